@@ -29,7 +29,7 @@ EXTENSIONS = [
 ]
 
 SKIP_DIRS = [
-    str(Path.cwd()) + "/templates/rust-starter",
+    str(Path.cwd()) + "/risc0/cargo-risczero/templates/rust-starter",
 ]
 
 def check_header(expected_year, lines_actual):
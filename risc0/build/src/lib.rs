@@ -26,14 +26,14 @@ use std::{
     process::{Command, Stdio},
 };
 
-use cargo_metadata::{MetadataCommand, Package};
+use cargo_metadata::{DependencyKind, MetadataCommand, Package};
 use downloader::{Download, Downloader};
 use risc0_zkvm::{
     sha::{Digest, DIGEST_WORDS},
     MemoryImage, Program,
 };
 use risc0_zkvm_platform::{memory::MEM_SIZE, PAGE_SIZE};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest as ShaDigest, Sha256};
 use tempfile::tempdir_in;
 use zip::ZipArchive;
@@ -41,9 +41,22 @@ use zip::ZipArchive;
 const LINKER_SCRIPT: &str = include_str!("../risc0.ld");
 const TARGET_JSON: &str = include_str!("../riscv32im-risc0-zkvm-elf.json");
 
+/// The toolchain used for reproducible guest builds.
+pub const REPRODUCIBLE_TOOLCHAIN: &str = "nightly-2022-10-28";
+
+/// The name of the file, written next to `methods.rs`, that holds a
+/// [BuildManifest] for each guest package.
+pub const BUILD_MANIFEST_FILE: &str = "methods.manifest.json";
+
 #[derive(Debug, Deserialize)]
 struct Risc0Metadata {
+    #[serde(default)]
     methods: Vec<String>,
+
+    /// Paths or `file://` URLs of ELFs built outside of risc0-build, relative
+    /// to the package directory.
+    #[serde(default)]
+    elfs: Vec<String>,
 }
 
 impl Risc0Metadata {
@@ -57,6 +70,10 @@ impl Risc0Metadata {
 struct Risc0Method {
     name: String,
     elf_path: PathBuf,
+
+    /// Where image IDs are cached, keyed by the hash of the ELF. Prebuilt
+    /// ELFs live outside of any target directory and are not cached.
+    image_id_cache: Option<PathBuf>,
 }
 
 impl Risc0Method {
@@ -69,10 +86,68 @@ impl Risc0Method {
             std::process::exit(-1);
         }
 
+        // Computing an image ID builds the full memory image, so image IDs are
+        // cached by the hash of the ELF they were computed from.
         let elf = fs::read(&self.elf_path).unwrap();
-        let program = Program::load_elf(&elf, MEM_SIZE as u32).unwrap();
+        let (_, elf_sha256) = sha_digest_with_hex(&elf);
+        let cache_path = self
+            .image_id_cache
+            .as_ref()
+            .map(|dir| dir.join(format!("{elf_sha256}.json")));
+        let cached = cache_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        if let Some(image_id) = cached {
+            return image_id;
+        }
+
+        let program = Program::load_elf(&elf, MEM_SIZE as u32).unwrap_or_else(|err| {
+            panic!(
+                "Invalid RISC-V method {}: {err}",
+                self.elf_path.to_str().unwrap()
+            )
+        });
         let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
-        image.get_root()
+        let image_id = image.get_root();
+
+        // Write to a temporary file first so that a concurrent build never
+        // reads a partial entry.
+        if let Some(cache_path) = cache_path {
+            fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+            let tmp_path = cache_path.with_extension("tmp");
+            fs::write(&tmp_path, serde_json::to_string(&image_id).unwrap()).unwrap();
+            fs::rename(&tmp_path, &cache_path).unwrap();
+        }
+        image_id
+    }
+
+    // Emits a `rerun-if-changed` line for the ELF and each source file it was
+    // built from, as listed in the dep-info file cargo writes next to it.
+    fn rerun_if_changed(&self) {
+        println!("cargo:rerun-if-changed={}", self.elf_path.display());
+        let dep_info_path = self.elf_path.with_extension("d");
+        match fs::read_to_string(&dep_info_path) {
+            Ok(dep_info) => {
+                for path in parse_dep_info(&dep_info) {
+                    println!("cargo:rerun-if-changed={}", path.display());
+                }
+            }
+            Err(_) => println!(
+                "cargo:warning=No dep-info found at {}; guest changes will not be detected",
+                dep_info_path.display()
+            ),
+        }
+    }
+
+    fn manifest(&self) -> MethodManifest {
+        let elf = fs::read(&self.elf_path).unwrap();
+        let (_, elf_sha256) = sha_digest_with_hex(&elf);
+        MethodManifest {
+            name: self.name.clone(),
+            elf_sha256,
+            image_id: self.make_image_id().to_string(),
+        }
     }
 
     fn rust_def(&self) -> String {
@@ -85,8 +160,7 @@ impl Risc0Method {
             panic!("method path cannot include #: {}", elf_path);
         }
 
-        let upper = self.name.to_uppercase();
-        let upper = upper.replace('-', "_");
+        let upper = self.const_name();
         let image_id: [u32; DIGEST_WORDS] = self.make_image_id().into();
         let elf_contents = std::fs::read(&self.elf_path).unwrap();
         format!(
@@ -97,6 +171,82 @@ pub const {upper}_PATH: &str = r#"{elf_path}"#;
             "##
         )
     }
+
+    fn const_name(&self) -> String {
+        self.name.to_uppercase().replace('-', "_")
+    }
+
+    // Defines a `risc0_zkvm::GuestMethod` in terms of the constants emitted by
+    // `rust_def`.
+    fn guest_method_def(&self, manifest_sha256: &str) -> String {
+        let name = &self.name;
+        let upper = self.const_name();
+        if upper == "METHODS" {
+            panic!(
+                "The guest method {name:?} would be defined as `METHODS`, which is reserved \
+                for the list of all methods; please rename it"
+            );
+        }
+        format!(
+            r##"
+pub const {upper}: risc0_zkvm::GuestMethod = risc0_zkvm::GuestMethod {{
+    name: "{name}",
+    elf: {upper}_ELF,
+    image_id: risc0_zkvm::sha::Digest::new({upper}_ID),
+    path: {upper}_PATH,
+    manifest_sha256: "{manifest_sha256}",
+}};
+            "##
+        )
+    }
+}
+
+/// A record of how the methods of a guest package were built, which allows a
+/// third party to rebuild them and check that the image IDs match.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BuildManifest {
+    /// The name of the guest package.
+    pub package: String,
+
+    /// The version of the Rust toolchain, as reported by `rustc -V`.
+    pub toolchain: String,
+
+    /// Identifies the standard library sources the guest was built against.
+    pub std_source: String,
+
+    /// The features the guest was built with.
+    pub features: Vec<String>,
+
+    /// Whether standard library support was enabled.
+    pub std: bool,
+
+    /// Whether the guest was built in reproducible mode.
+    pub reproducible: bool,
+
+    /// The cargo profile the guest was built with.
+    pub profile: String,
+
+    /// The extra rustflags the guest was built with.
+    pub rustflags: Vec<String>,
+
+    /// The extra environment variables the guest was built with.
+    pub env: Vec<(String, String)>,
+
+    /// The methods built from the guest package.
+    pub methods: Vec<MethodManifest>,
+}
+
+/// A record of a single method in a [BuildManifest].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MethodManifest {
+    /// The name of the method.
+    pub name: String,
+
+    /// The SHA-256 of the ELF, as hex.
+    pub elf_sha256: String,
+
+    /// The image ID of the method, as hex.
+    pub image_id: String,
 }
 
 #[derive(Debug)]
@@ -105,6 +255,10 @@ struct ZipMapEntry {
     zip_url: &'static str,
     src_prefix: &'static str,
     dst_prefix: &'static str,
+    // Expected SHA-256 of the zip, as hex. Archives without one are accepted
+    // with a warning reporting the digest of the fetched zip, so that it can
+    // be pinned.
+    sha256: Option<&'static str>,
 }
 
 // Sources for standard library, and where they should be mapped to.
@@ -113,22 +267,33 @@ const RUST_LIB_MAP : &[ZipMapEntry] = &[
         filename: "53bbc8fc2afb2e10e3a90d7bf188bfd6598374ab.zip",
         zip_url: "https://github.com/risc0/rust/archive/53bbc8fc2afb2e10e3a90d7bf188bfd6598374ab.zip",
         src_prefix: "rust-53bbc8fc2afb2e10e3a90d7bf188bfd6598374ab/library",
-        dst_prefix: "library"
+        dst_prefix: "library",
+        // TODO: pin the SHA-256 of the published archive.
+        sha256: None,
     },
     ZipMapEntry {
         filename: "790411f93c4b5eada3c23abb4c9a063fb0b24d99.zip",
         zip_url: "https://github.com/rust-lang/stdarch/archive/790411f93c4b5eada3c23abb4c9a063fb0b24d99.zip",
         src_prefix:"stdarch-790411f93c4b5eada3c23abb4c9a063fb0b24d99",
-        dst_prefix: "library/stdarch"
+        dst_prefix: "library/stdarch",
+        // TODO: pin the SHA-256 of the published archive.
+        sha256: None,
     },
     ZipMapEntry {
         filename: "07872f28cd8a65c3c7428811548dc85f1f2fb05b.zip",
         zip_url: "https://github.com/rust-lang/backtrace-rs/archive/07872f28cd8a65c3c7428811548dc85f1f2fb05b.zip",
         src_prefix:"backtrace-rs-07872f28cd8a65c3c7428811548dc85f1f2fb05b",
-        dst_prefix: "library/backtrace"
+        dst_prefix: "library/backtrace",
+        // TODO: pin the SHA-256 of the published archive.
+        sha256: None,
     },
 ];
 
+// The RISC0_STD_ARCHIVES variable can point at a local mirror directory
+// containing the RUST_LIB_MAP zips, or at a bundle zip written by
+// `bundle_std`, so that guests can be built without network access.
+const STD_ARCHIVES_ENV: &str = "RISC0_STD_ARCHIVES";
+
 fn sha_digest_with_hex(data: &[u8]) -> (Vec<u8>, String) {
     let bin_sha = Sha256::new().chain_update(data).finalize();
     (
@@ -194,29 +359,126 @@ fn guest_packages(pkg: &Package) -> Vec<Package> {
         .collect()
 }
 
+/// Returns the prebuilt ELFs specified in the "elfs" list inside
+/// "package.metadata.risc0".
+fn prebuilt_methods(pkg: &Package) -> Vec<Risc0Method> {
+    let manifest_dir = pkg.manifest_path.parent().unwrap();
+    Risc0Metadata::from_package(pkg)
+        .unwrap()
+        .elfs
+        .iter()
+        .map(|elf| {
+            let elf = elf.strip_prefix("file://").unwrap_or(elf);
+            if elf.contains("://") {
+                panic!("Only local files are supported for prebuilt ELFs: {elf}");
+            }
+            let elf_path = manifest_dir.join(elf).into_std_path_buf();
+            let name = elf_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_else(|| panic!("Invalid prebuilt ELF path: {elf}"))
+                .to_string();
+            Risc0Method {
+                name,
+                elf_path,
+                image_id_cache: None,
+            }
+        })
+        .collect()
+}
+
 /// Returns all methods associated with the given riscv guest package.
-fn guest_methods<P>(pkg: &Package, target_dir: P) -> Vec<Risc0Method>
+fn guest_methods<P>(pkg: &Package, target_dir: P, profile: &str) -> Vec<Risc0Method>
 where
     P: AsRef<Path>,
 {
+    let elf_dir = target_dir.as_ref().join("riscv32im-risc0-zkvm-elf");
+    // Keyed by the version of risc0-build, as the memory image layout may
+    // change between versions.
+    let image_id_cache = elf_dir.join("image-ids").join(env!("CARGO_PKG_VERSION"));
     pkg.targets
         .iter()
         .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
         .map(|target| Risc0Method {
             name: target.name.clone(),
-            elf_path: target_dir
-                .as_ref()
-                .join("riscv32im-risc0-zkvm-elf")
-                .join("release")
-                .join(&target.name),
+            elf_path: elf_dir.join(profile_dir(profile)).join(&target.name),
+            image_id_cache: Some(image_id_cache.clone()),
         })
         .collect()
 }
 
+/// Returns the dependencies of the first rule of a makefile-style dep-info
+/// file, in which spaces within paths are escaped with a backslash and dollar
+/// signs are doubled. Other backslashes, as in Windows paths, are literal.
+fn parse_dep_info(dep_info: &str) -> Vec<PathBuf> {
+    let line = dep_info.lines().next().unwrap_or_default();
+    let deps = match line.find(": ") {
+        Some(pos) => &line[pos + 2..],
+        None => return vec![],
+    };
+    let mut paths = vec![];
+    let mut path = String::new();
+    let mut chars = deps.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&' ') => path.push(chars.next().unwrap()),
+            '$' if chars.peek() == Some(&'$') => path.push(chars.next().unwrap()),
+            ' ' => {
+                if !path.is_empty() {
+                    paths.push(PathBuf::from(std::mem::take(&mut path)));
+                }
+            }
+            c => path.push(c),
+        }
+    }
+    if !path.is_empty() {
+        paths.push(PathBuf::from(path));
+    }
+    paths
+}
+
+/// Returns the root of the workspace containing the given package, which is
+/// where its Cargo.lock lives.
+fn workspace_root(pkg: &Package) -> PathBuf {
+    MetadataCommand::new()
+        .manifest_path(&pkg.manifest_path)
+        .no_deps()
+        .exec()
+        .unwrap()
+        .workspace_root
+        .into_std_path_buf()
+}
+
+/// Returns the name of the directory that cargo places the output of the
+/// given profile in.
+fn profile_dir(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    }
+}
+
+/// Returns the directory that guests are built into by default, which is
+/// `riscv-guest` in the profile directory of the package whose build script is
+/// running.
+fn guest_target_dir(out_dir: &Path) -> PathBuf {
+    // OUT_DIR is $target/[$triple/]$profile/build/$crate-$hash/out
+    let build_dir = out_dir
+        .ancestors()
+        .find(|dir| dir.file_name().map_or(false, |name| name == "build"))
+        .unwrap_or_else(|| panic!("Unexpected OUT_DIR layout: {}", out_dir.display()));
+    build_dir
+        .parent() // $profile
+        .unwrap()
+        .join("riscv-guest")
+}
+
 #[derive(Debug)]
 struct GuestBuildEnv {
     target_spec: PathBuf,
     rust_lib_src: PathBuf,
+    std_source: String,
 }
 
 fn setup_guest_build_env<P>(out_dir: P) -> GuestBuildEnv
@@ -236,9 +498,10 @@ where
     // want to have a different hash so that we make sure we recompile.
     let (_, src_id_hash) = sha_digest_with_hex(format!("{:?}", RUST_LIB_MAP).as_bytes());
     let rust_lib_path = out_dir.as_ref().join(format!("rust-std_{}", src_id_hash));
+    println!("cargo:rerun-if-env-changed={STD_ARCHIVES_ENV}");
     if !rust_lib_path.exists() {
         println!(
-            "Standard library {} does not exist; downloading",
+            "Standard library {} does not exist; fetching",
             rust_lib_path.display()
         );
 
@@ -248,6 +511,7 @@ where
     GuestBuildEnv {
         target_spec: target_spec_path.to_owned(),
         rust_lib_src: rust_lib_path,
+        std_source: src_id_hash,
     }
 }
 
@@ -258,6 +522,147 @@ fn risc0_cache() -> PathBuf {
         .into()
 }
 
+fn std_archives_source() -> Option<PathBuf> {
+    env::var_os(STD_ARCHIVES_ENV).map(PathBuf::from)
+}
+
+// Checks the zip at `zip_path` against its pinned digest. Returns the digest.
+fn verify_archive(zm: &ZipMapEntry, zip_path: &Path) -> Result<String, String> {
+    let (_, actual) = sha_digest_with_hex(&fs::read(zip_path).unwrap());
+    let expected = match zm.sha256 {
+        Some(expected) => expected,
+        None => {
+            println!(
+                "cargo:warning={} has no pinned SHA-256; using it unverified (SHA-256 {actual})",
+                zm.filename
+            );
+            return Ok(actual);
+        }
+    };
+    if actual != expected {
+        return Err(format!(
+            "{} has SHA-256 {actual}, expected {expected}",
+            zip_path.display()
+        ));
+    }
+    Ok(actual)
+}
+
+// Copies the archive for `zm` from a local mirror directory or bundle zip
+// into `dest`, returning false if the source does not contain it.
+fn copy_archive_from(zm: &ZipMapEntry, source: &Path, dest: &Path) -> bool {
+    if source.is_dir() {
+        let src_path = source.join(zm.filename);
+        if !src_path.is_file() {
+            return false;
+        }
+        fs::copy(src_path, dest).unwrap();
+        return true;
+    }
+
+    let bundle = File::open(source)
+        .unwrap_or_else(|err| panic!("Unable to open {}: {err}", source.display()));
+    let mut bundle = ZipArchive::new(bundle)
+        .unwrap_or_else(|err| panic!("{} is not a zip bundle: {err}", source.display()));
+    let mut f = match bundle.by_name(zm.filename) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+    std::io::copy(&mut f, &mut File::create(dest).unwrap()).unwrap();
+    true
+}
+
+// Ensures the archive for `zm` is in `cache_dir` and matches its pin, taking
+// it from `source` if given and otherwise downloading it. Returns the path
+// and digest of the cached zip.
+fn fetch_archive(zm: &ZipMapEntry, cache_dir: &Path, source: Option<&Path>) -> (PathBuf, String) {
+    let zip_path = cache_dir.join(zm.filename);
+    if zip_path.is_file() {
+        match verify_archive(zm, &zip_path) {
+            Ok(sha256) => return (zip_path, sha256),
+            Err(err) => {
+                println!("Discarding cached archive: {err}");
+                fs::remove_file(&zip_path).unwrap();
+            }
+        }
+    }
+
+    let temp_dir = tempdir_in(cache_dir).unwrap();
+    let temp_path = temp_dir.path().join(zm.filename);
+    match source {
+        Some(source) => {
+            println!("Copying {} from {}", zm.filename, source.display());
+            if !copy_archive_from(zm, source, &temp_path) {
+                panic!("{} does not contain {}", source.display(), zm.filename);
+            }
+        }
+        None => {
+            println!("Downloading {}", zm.zip_url);
+            let mut downloader = Downloader::builder()
+                .download_folder(temp_dir.path())
+                .build()
+                .unwrap();
+            let dl = Download::new(zm.zip_url);
+            downloader.download(&[dl]).unwrap().iter().for_each(|x| {
+                let summary = x.as_ref().unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to download {}: {err}. To build without network access, \
+                        set {STD_ARCHIVES_ENV} to a mirror directory or a bundle written by \
+                        `cargo risczero fetch-std --bundle`.",
+                        zm.zip_url
+                    )
+                });
+                println!("Downloaded: {}", summary.file_name.display());
+            });
+        }
+    }
+    let sha256 = verify_archive(zm, &temp_path)
+        .unwrap_or_else(|err| panic!("Rejecting {}: {err}", zm.filename));
+    fs::rename(&temp_path, &zip_path).unwrap();
+    (zip_path, sha256)
+}
+
+/// Populates the local cache with the sources of the RISC Zero fork of the
+/// Rust standard library, so that guests can later be built without network
+/// access.
+///
+/// If `source` is given, it must be a local mirror directory containing the
+/// archives, or a bundle written by [bundle_std]; otherwise the archives are
+/// downloaded. Each archive with a pinned SHA-256 is checked against it.
+/// Returns the file name and SHA-256 of each archive.
+pub fn fetch_std(source: Option<&Path>) -> Vec<(String, String)> {
+    let cache_dir = risc0_cache();
+    fs::create_dir_all(&cache_dir).unwrap();
+    RUST_LIB_MAP
+        .iter()
+        .map(|zm| {
+            let (_, sha256) = fetch_archive(zm, &cache_dir, source);
+            (zm.filename.to_string(), sha256)
+        })
+        .collect()
+}
+
+/// Writes the cached standard library archives to a single zip bundle at
+/// `dest`, which can be copied to machines without network access and used
+/// with [fetch_std] or by setting `RISC0_STD_ARCHIVES`.
+pub fn bundle_std(dest: &Path) {
+    let cache_dir = risc0_cache();
+    fs::create_dir_all(&cache_dir).unwrap();
+    let mut bundle = zip::ZipWriter::new(File::create(dest).unwrap());
+    for zm in RUST_LIB_MAP.iter() {
+        let (zip_path, _) = fetch_archive(zm, &cache_dir, std_archives_source().as_deref());
+        bundle
+            .start_file(
+                zm.filename,
+                zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        std::io::copy(&mut File::open(zip_path).unwrap(), &mut bundle).unwrap();
+    }
+    bundle.finish().unwrap();
+}
+
 fn download_zip_map<P>(zip_map: &[ZipMapEntry], dest_base: P)
 where
     P: AsRef<Path>,
@@ -266,12 +671,7 @@ where
     if !cache_dir.is_dir() {
         fs::create_dir_all(&cache_dir).unwrap();
     }
-
-    let temp_dir = tempdir_in(&cache_dir).unwrap();
-    let mut downloader = Downloader::builder()
-        .download_folder(temp_dir.path())
-        .build()
-        .unwrap();
+    let source = std_archives_source();
 
     let tmp_dest_base = dest_base.as_ref().with_extension("downloadtmp");
     if tmp_dest_base.exists() {
@@ -283,21 +683,8 @@ where
         let dst_prefix = tmp_dest_base.join(&zm.dst_prefix);
         fs::create_dir_all(&dst_prefix).unwrap();
 
-        let zip_path = cache_dir.join(zm.filename);
-        if !zip_path.is_file() {
-            println!(
-                "Downloading {}, mapping {} to {}",
-                zm.zip_url,
-                zm.src_prefix,
-                dst_prefix.display()
-            );
-            let dl = Download::new(zm.zip_url);
-            downloader.download(&[dl]).unwrap().iter().for_each(|x| {
-                let summary = x.as_ref().unwrap();
-                println!("Downloaded: {}", summary.file_name.display());
-            });
-            fs::rename(temp_dir.path().join(zm.filename), &zip_path).unwrap();
-        }
+        let (zip_path, _) = fetch_archive(zm, &cache_dir, source.as_deref());
+        println!("Mapping {} to {}", zm.src_prefix, dst_prefix.display());
 
         let zip_file = File::open(zip_path).unwrap();
         let mut zip = ZipArchive::new(zip_file).unwrap();
@@ -325,14 +712,33 @@ where
     fs::rename(&tmp_dest_base, dest_base.as_ref()).unwrap();
 }
 
+// Returns the version of the given toolchain, or of the toolchain in use if
+// `toolchain` is None.
+fn rustc_version(toolchain: Option<&str>) -> String {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let mut cmd = match toolchain {
+        Some(toolchain) => {
+            let mut cmd = Command::new("rustc");
+            cmd.env("RUSTUP_TOOLCHAIN", toolchain);
+            cmd
+        }
+        None => Command::new(rustc),
+    };
+    let output = cmd.arg("-V").output().expect("Failed to run rustc -V");
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
 // Builds a package that targets the riscv guest into the specified target
 // directory.
+//
+// In reproducible mode, the guest is built with REPRODUCIBLE_TOOLCHAIN, in an
+// environment containing only what is needed to locate the toolchain, and
+// with every host-specific path prefix remapped to a fixed value.
 fn build_guest_package<P>(
     pkg: &Package,
     target_dir: P,
     guest_build_env: &GuestBuildEnv,
-    features: Vec<String>,
-    std: bool,
+    options: &GuestOptions,
 ) where
     P: AsRef<Path>,
 {
@@ -343,15 +749,24 @@ fn build_guest_package<P>(
     }
 
     fs::create_dir_all(target_dir.as_ref()).unwrap();
-    let cargo = env::var("CARGO").unwrap();
+    let cargo = if options.reproducible {
+        // Go through the rustup proxy so that RUSTUP_TOOLCHAIN selects the
+        // toolchain.
+        "cargo".to_string()
+    } else {
+        env::var("CARGO").unwrap()
+    };
     let mut std_parts = vec!["alloc", "core", "proc_macro", "panic_abort"];
-    if std {
+    if options.std {
         std_parts.push("std");
     }
     let build_std = format!("build-std={}", std_parts.join(","));
-    let mut args = vec![
-        "build",
-        "--release",
+    let mut args = vec!["build"];
+    match options.profile() {
+        "release" => args.push("--release"),
+        profile => args.extend(["--profile", profile]),
+    }
+    args.extend([
         "--target",
         guest_build_env.target_spec.to_str().unwrap(),
         "-Z",
@@ -362,9 +777,9 @@ fn build_guest_package<P>(
         pkg.manifest_path.as_str(),
         "--target-dir",
         target_dir.as_ref().to_str().unwrap(),
-    ];
-    let features_str = features.join(",");
-    if !features.is_empty() {
+    ]);
+    let features_str = options.features.join(",");
+    if !options.features.is_empty() {
         args.push("--features");
         args.push(&features_str);
     }
@@ -380,20 +795,51 @@ fn build_guest_package<P>(
 
     println!("Using rust standard library root: {}", risc0_standard_lib);
 
+    let mut rustflags = vec![
+        // Replace atomic ops with nonatomic versions since the guest is single threaded.
+        "-C".to_string(),
+        "passes=loweratomic".to_string(),
+        // Remap absolute pathnames in compiled ELFs for builds that are more reproducible.
+        "-Z".to_string(),
+        "remap-cwd-prefix=.".to_string(),
+    ];
+    rustflags.extend(options.rustflags.iter().cloned());
+
     let mut cmd = Command::new(cargo);
+    if options.reproducible {
+        let guest_dir = pkg.manifest_path.parent().unwrap();
+        let mut remaps = vec![
+            (risc0_standard_lib.clone(), "/rust-std".to_string()),
+            (guest_dir.to_string(), "/guest".to_string()),
+            (
+                target_dir.as_ref().to_str().unwrap().to_string(),
+                "/target".to_string(),
+            ),
+        ];
+        if let Some(cargo_home) = cargo_home() {
+            remaps.push((
+                cargo_home.to_str().unwrap().to_string(),
+                "/cargo".to_string(),
+            ));
+        }
+        for (from, to) in remaps {
+            rustflags.push(format!("--remap-path-prefix={from}={to}"));
+        }
+
+        cmd.env_clear();
+        for var in ["PATH", "HOME", "CARGO_HOME", "RUSTUP_HOME"] {
+            if let Some(value) = env::var_os(var) {
+                cmd.env(var, value);
+            }
+        }
+        cmd.env("RUSTUP_TOOLCHAIN", REPRODUCIBLE_TOOLCHAIN)
+            .env("CARGO_INCREMENTAL", "0")
+            .env("SOURCE_DATE_EPOCH", "0");
+    }
+
     let mut child = cmd
-        .env(
-            "CARGO_ENCODED_RUSTFLAGS",
-            [
-                // Replace atomic ops with nonatomic versions since the guest is single threaded.
-                "-C",
-                "passes=loweratomic",
-                // Remap absolute pathnames in compiled ELFs for builds that are more reproducible.
-                "-Z",
-                "remap-cwd-prefix=.",
-            ]
-            .join("\x1f"),
-        )
+        .envs(options.env.iter().cloned())
+        .env("CARGO_ENCODED_RUSTFLAGS", rustflags.join("\x1f"))
         .env("__CARGO_TESTS_ONLY_SRC_ROOT", risc0_standard_lib)
         .args(args)
         .stderr(Stdio::piped())
@@ -436,14 +882,148 @@ fn build_guest_package<P>(
     }
 }
 
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))
+}
+
+// Builds the guest package and returns a manifest describing the result.
+fn build_guest_manifest<P>(
+    pkg: &Package,
+    target_dir: P,
+    guest_build_env: &GuestBuildEnv,
+    options: &GuestOptions,
+) -> BuildManifest
+where
+    P: AsRef<Path>,
+{
+    build_guest_package(pkg, &target_dir, guest_build_env, options);
+    let std_source = match env::var("RISC0_STANDARD_LIB") {
+        Ok(path) => format!("path:{path}"),
+        Err(_) => guest_build_env.std_source.clone(),
+    };
+    BuildManifest {
+        package: pkg.name.clone(),
+        toolchain: rustc_version(options.reproducible.then_some(REPRODUCIBLE_TOOLCHAIN)),
+        std_source,
+        features: options.features.clone(),
+        std: options.std,
+        reproducible: options.reproducible,
+        profile: options.profile().to_string(),
+        rustflags: options.rustflags.clone(),
+        env: options.env.clone(),
+        methods: guest_methods(pkg, &target_dir, options.profile())
+            .iter()
+            .map(Risc0Method::manifest)
+            .collect(),
+    }
+}
+
+/// Rebuilds the guest package at `manifest_dir` in reproducible mode with the
+/// options recorded in `expected`, and returns the resulting manifest.
+///
+/// The image IDs of the returned manifest can be compared against `expected`
+/// to check that a guest was built from the given sources.
+pub fn rebuild_guest(manifest_dir: &Path, expected: &BuildManifest) -> BuildManifest {
+    let pkg = get_package(manifest_dir);
+    let build_dir = tempfile::tempdir().unwrap();
+    let guest_build_env = setup_guest_build_env(build_dir.path());
+    let options = GuestOptions {
+        features: expected.features.clone(),
+        std: expected.std,
+        reproducible: true,
+        profile: Some(expected.profile.clone()),
+        rustflags: expected.rustflags.clone(),
+        env: expected.env.clone(),
+        target_dir: None,
+    };
+    build_guest_manifest(
+        &pkg,
+        build_dir.path().join("riscv-guest"),
+        &guest_build_env,
+        &options,
+    )
+}
+
+/// A guest method built by [build_guest].
+#[derive(Clone, Debug)]
+pub struct BuiltMethod {
+    /// The name of the method, which is the name of its binary target.
+    pub name: String,
+
+    /// The path to the method's ELF binary.
+    pub elf_path: PathBuf,
+
+    /// The image ID of the method.
+    pub image_id: Digest,
+}
+
+/// Builds the guest package at `manifest_dir` outside of a build script, and
+/// returns each of its methods.
+///
+/// The guest is built in [GuestOptions::target_dir], which defaults to
+/// `target/riscv-guest` in `manifest_dir`.
+pub fn build_guest(manifest_dir: &Path, options: &GuestOptions) -> Vec<BuiltMethod> {
+    // cargo reports absolute manifest paths, which a relative directory
+    // would not match.
+    let manifest_dir = manifest_dir.canonicalize().unwrap();
+    let pkg = get_package(&manifest_dir);
+    let target_dir = options
+        .target_dir
+        .clone()
+        .unwrap_or_else(|| manifest_dir.join("target").join("riscv-guest"));
+    fs::create_dir_all(&target_dir).unwrap();
+    let guest_build_env = setup_guest_build_env(&target_dir);
+    build_guest_package(&pkg, &target_dir, &guest_build_env, options);
+    guest_methods(&pkg, &target_dir, options.profile())
+        .into_iter()
+        .map(|method| BuiltMethod {
+            image_id: method.make_image_id(),
+            name: method.name,
+            elf_path: method.elf_path,
+        })
+        .collect()
+}
+
 /// Options defining how to embed a guest package in
 /// [`embed_methods_with_options`].
+#[derive(Clone, Debug)]
 pub struct GuestOptions {
     /// Features for cargo to build the guest with.
     pub features: Vec<String>,
 
     /// Enable standard library support
     pub std: bool,
+
+    /// Build with a pinned toolchain, a minimal environment and normalized
+    /// paths, so that third parties can reproduce the image IDs with `cargo
+    /// risczero verify-build`.
+    pub reproducible: bool,
+
+    /// The cargo profile to build the guest with. Defaults to `release`.
+    ///
+    /// For example, a custom profile with `debug = true` produces guests with
+    /// debug symbols for use with the profiler.
+    pub profile: Option<String>,
+
+    /// Extra flags passed to rustc, such as `-C opt-level=z`. These are added
+    /// after the flags that risc0-build always passes.
+    pub rustflags: Vec<String>,
+
+    /// Extra environment variables set when building the guest.
+    pub env: Vec<(String, String)>,
+
+    /// The directory to build the guest in. Defaults to `riscv-guest` in the
+    /// profile directory of the host package, e.g.
+    /// `target/release/riscv-guest`.
+    pub target_dir: Option<PathBuf>,
+}
+
+impl GuestOptions {
+    fn profile(&self) -> &str {
+        self.profile.as_deref().unwrap_or("release")
+    }
 }
 
 impl Default for GuestOptions {
@@ -451,6 +1031,11 @@ impl Default for GuestOptions {
         GuestOptions {
             features: vec![],
             std: true,
+            reproducible: false,
+            profile: None,
+            rustflags: vec![],
+            env: vec![],
+            target_dir: None,
         }
     }
 }
@@ -461,23 +1046,29 @@ impl Default for GuestOptions {
 pub fn embed_methods_with_options(mut guest_pkg_to_options: HashMap<&str, GuestOptions>) {
     let out_dir_env = env::var_os("OUT_DIR").unwrap();
     let out_dir = Path::new(&out_dir_env); // $ROOT/target/$profile/build/$crate/out
-    let guest_dir = out_dir
-        .parent() // out
-        .unwrap()
-        .parent() // $crate
-        .unwrap()
-        .parent() // build
-        .unwrap()
-        .parent() // $profile
-        .unwrap()
-        .join("riscv-guest");
+    let default_guest_dir = guest_target_dir(out_dir);
 
     let pkg = current_package();
     let guest_packages = guest_packages(&pkg);
     let methods_path = out_dir.join("methods.rs");
-    let mut methods_file = File::create(&methods_path).unwrap();
+    let mut methods_rs = String::new();
+    let mut manifests = Vec::new();
+    let mut method_names = Vec::new();
 
-    let guest_build_env = setup_guest_build_env(&out_dir);
+    // The typed descriptors refer to risc0-zkvm, which older methods packages
+    // do not depend on. A build or dev dependency is not visible to the
+    // generated code.
+    let typed_methods = pkg
+        .dependencies
+        .iter()
+        .any(|dep| dep.name == "risc0-zkvm" && dep.kind == DependencyKind::Normal);
+
+    // Packages that only embed prebuilt ELFs need neither the target spec nor
+    // the standard library sources.
+    let guest_build_env = (!guest_packages.is_empty()).then(|| setup_guest_build_env(out_dir));
+
+    println!("cargo:rerun-if-changed={}", pkg.manifest_path);
+    println!("cargo:rerun-if-env-changed=RISC0_STANDARD_LIB");
 
     for guest_pkg in guest_packages {
         println!("Building guest package {}.{}", pkg.name, guest_pkg.name);
@@ -486,28 +1077,77 @@ pub fn embed_methods_with_options(mut guest_pkg_to_options: HashMap<&str, GuestO
             .remove(guest_pkg.name.as_str())
             .unwrap_or_default();
 
-        build_guest_package(
+        let guest_dir = guest_options
+            .target_dir
+            .clone()
+            .unwrap_or_else(|| default_guest_dir.clone());
+
+        let manifest = build_guest_manifest(
             &guest_pkg,
             &guest_dir,
-            &guest_build_env,
-            guest_options.features,
-            guest_options.std,
+            guest_build_env.as_ref().unwrap(),
+            &guest_options,
         );
+        let (_, manifest_sha256) =
+            sha_digest_with_hex(serde_json::to_string(&manifest).unwrap().as_bytes());
+        manifests.push(manifest);
 
-        for method in guest_methods(&guest_pkg, &guest_dir) {
-            methods_file
-                .write_all(method.rust_def().as_bytes())
-                .unwrap();
+        // The dep-info files list the sources of the guest, but not the
+        // manifests that select them.
+        println!("cargo:rerun-if-changed={}", guest_pkg.manifest_path);
+        let guest_lock_path = workspace_root(&guest_pkg).join("Cargo.lock");
+        if guest_lock_path.exists() {
+            println!("cargo:rerun-if-changed={}", guest_lock_path.display());
+        }
+
+        for method in guest_methods(&guest_pkg, &guest_dir, guest_options.profile()) {
+            method.rerun_if_changed();
+            methods_rs.push_str(&method.rust_def());
+            if typed_methods {
+                methods_rs.push_str(&method.guest_method_def(&manifest_sha256));
+            }
+            method_names.push(method.const_name());
+        }
+    }
+
+    // Prebuilt ELFs have no build manifest, and are only rebuilt by whatever
+    // produced them.
+    for method in prebuilt_methods(&pkg) {
+        println!("Embedding prebuilt ELF {}", method.elf_path.display());
+        println!("cargo:rerun-if-changed={}", method.elf_path.display());
+        methods_rs.push_str(&method.rust_def());
+        if typed_methods {
+            methods_rs.push_str(&method.guest_method_def(""));
         }
+        method_names.push(method.const_name());
     }
 
-    // HACK: It's not particularly practical to figure out all the
-    // files that all the guest crates transtively depend on.  So, we
-    // want to run the guest "cargo build" command each time we build.
-    //
-    // Since we generate methods.rs each time we run, it will always
-    // be changed.
-    println!("cargo:rerun-if-changed={}", methods_path.display());
+    if typed_methods {
+        let methods = method_names.join(", ");
+        methods_rs.push_str(&format!(
+            r##"
+pub const METHODS: &[risc0_zkvm::GuestMethod] = &[{methods}];
+
+pub fn method(name: &str) -> Option<&'static risc0_zkvm::GuestMethod> {{
+    risc0_zkvm::GuestMethod::find(METHODS, name)
+}}
+            "##
+        ));
+    }
+
+    // Only touch the outputs when they change, so that the crate including
+    // methods.rs is not recompiled needlessly.
+    write_if_changed(&methods_path, methods_rs.as_bytes());
+    write_if_changed(
+        &out_dir.join(BUILD_MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifests).unwrap().as_bytes(),
+    );
+}
+
+fn write_if_changed(path: &Path, contents: &[u8]) {
+    if fs::read(path).map_or(true, |existing| existing != contents) {
+        fs::write(path, contents).unwrap();
+    }
 }
 
 /// Embeds methods built for RISC-V for use by host-side dependencies.
@@ -528,6 +1168,151 @@ pub fn embed_methods_with_options(mut guest_pkg_to_options: HashMap<&str, GuestO
 /// to uppercase.  For instance, if you have a method named
 /// "my_method", the image ID and elf contents will be defined as
 /// "MY_METHOD_ID" and "MY_METHOD_ELF" respectively.
+///
+/// If the package depends on `risc0-zkvm`, each method is also described by
+/// a [GuestMethod](risc0_zkvm::GuestMethod) constant, "MY_METHOD" in the
+/// example above. These are collected in a `METHODS` slice, and `method(name)`
+/// returns the one with the given name. A method can therefore not be named
+/// "methods".
 pub fn embed_methods() {
     embed_methods_with_options(HashMap::new())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use tempfile::tempdir;
+
+    use super::{
+        guest_target_dir, parse_dep_info, profile_dir, rebuild_guest, sha_digest_with_hex,
+        verify_archive, BuildManifest, ZipMapEntry,
+    };
+
+    fn zip_map_entry(sha256: Option<&'static str>) -> ZipMapEntry {
+        ZipMapEntry {
+            filename: "archive.zip",
+            zip_url: "https://example.com/archive.zip",
+            src_prefix: "archive",
+            dst_prefix: "library",
+            sha256,
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved for the list of all methods")]
+    fn reserved_method_name() {
+        let method = Risc0Method {
+            name: "methods".to_string(),
+            elf_path: PathBuf::from("methods"),
+            image_id_cache: None,
+        };
+        method.guest_method_def("");
+    }
+
+    #[test]
+    fn dep_info() {
+        let cases: &[(&str, &[&str])] = &[
+            ("", &[]),
+            ("out.elf:", &[]),
+            ("out.elf: src/main.rs", &["src/main.rs"]),
+            (
+                "out.elf: src/main.rs  src/lib.rs\n\nsrc/main.rs:\n",
+                &["src/main.rs", "src/lib.rs"],
+            ),
+            ("out.elf: my\\ guest/main.rs", &["my guest/main.rs"]),
+            ("out.elf: a\\ \\ b.rs c.rs", &["a  b.rs", "c.rs"]),
+            (
+                "out.elf: C:\\guest\\src\\main.rs",
+                &["C:\\guest\\src\\main.rs"],
+            ),
+            (
+                "out.elf: $$HOME/main.rs a$b.rs",
+                &["$HOME/main.rs", "a$b.rs"],
+            ),
+        ];
+        for (dep_info, expected) in cases {
+            let expected: Vec<_> = expected.iter().map(PathBuf::from).collect();
+            assert_eq!(parse_dep_info(dep_info), expected, "{dep_info:?}");
+        }
+    }
+
+    #[test]
+    fn profile_dirs() {
+        assert_eq!(profile_dir("dev"), "debug");
+        assert_eq!(profile_dir("test"), "debug");
+        assert_eq!(profile_dir("release"), "release");
+        assert_eq!(profile_dir("bench"), "release");
+        assert_eq!(profile_dir("profiling"), "profiling");
+    }
+
+    #[test]
+    fn guest_target_dirs() {
+        assert_eq!(
+            guest_target_dir(Path::new("/ws/target/release/build/methods-0123/out")),
+            Path::new("/ws/target/release/riscv-guest")
+        );
+        assert_eq!(
+            guest_target_dir(Path::new(
+                "/ws/target/x86_64-unknown-linux-gnu/debug/build/methods-0123/out"
+            )),
+            Path::new("/ws/target/x86_64-unknown-linux-gnu/debug/riscv-guest")
+        );
+    }
+
+    #[test]
+    fn verify_archive_digest() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("archive.zip");
+        fs::write(&zip_path, b"archive").unwrap();
+        let (_, digest) = sha_digest_with_hex(b"archive");
+        let digest: &'static str = Box::leak(digest.into_boxed_str());
+
+        assert_eq!(
+            verify_archive(&zip_map_entry(Some(digest)), &zip_path).unwrap(),
+            digest
+        );
+
+        // Unpinned archives are only warned about.
+        assert_eq!(
+            verify_archive(&zip_map_entry(None), &zip_path).unwrap(),
+            digest
+        );
+
+        let (_, other) = sha_digest_with_hex(b"other");
+        let other: &'static str = Box::leak(other.into_boxed_str());
+        let err = verify_archive(&zip_map_entry(Some(other)), &zip_path).unwrap_err();
+        assert!(err.contains(&format!("expected {other}")), "{err}");
+    }
+
+    // Needs REPRODUCIBLE_TOOLCHAIN and the standard library archives.
+    #[test]
+    #[ignore]
+    fn reproducible_build() {
+        let guest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../zkvm/methods/std")
+            .canonicalize()
+            .unwrap();
+        let options = BuildManifest {
+            package: "risc0-zkvm-methods-std".to_string(),
+            toolchain: String::new(),
+            std_source: String::new(),
+            features: vec!["test_feature1".to_string(), "test_feature2".to_string()],
+            std: true,
+            reproducible: true,
+            profile: "release".to_string(),
+            rustflags: vec![],
+            env: vec![],
+            methods: vec![],
+        };
+
+        // Each rebuild happens in a fresh temporary directory.
+        let first = rebuild_guest(&guest_dir, &options);
+        let second = rebuild_guest(&guest_dir, &options);
+        assert!(!first.methods.is_empty());
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Runs `embed_methods` as if from the build script of a package that only
+// embeds a prebuilt ELF. This sets process-wide environment variables, so it
+// lives in its own test binary.
+
+use std::{env, fs};
+
+use risc0_build::embed_methods;
+use risc0_zkvm::{sha::DIGEST_WORDS, MemoryImage, Program};
+use risc0_zkvm_platform::{
+    memory::{MEM_SIZE, TEXT},
+    PAGE_SIZE,
+};
+use tempfile::tempdir;
+
+// Returns a minimal 32-bit RISC-V executable with a single loadable segment
+// holding one `ecall` instruction at the start of the text region.
+fn minimal_elf() -> Vec<u8> {
+    const EHDR_SIZE: u16 = 52;
+    const PHDR_SIZE: u16 = 32;
+    let entry = TEXT.start() as u32;
+    let code = 0x0000_0073u32.to_le_bytes();
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+    elf.resize(16, 0);
+    elf.extend(2u16.to_le_bytes()); // e_type: ET_EXEC
+    elf.extend(243u16.to_le_bytes()); // e_machine: EM_RISCV
+    elf.extend(1u32.to_le_bytes()); // e_version
+    elf.extend(entry.to_le_bytes()); // e_entry
+    elf.extend((EHDR_SIZE as u32).to_le_bytes()); // e_phoff
+    elf.extend(0u32.to_le_bytes()); // e_shoff
+    elf.extend(0u32.to_le_bytes()); // e_flags
+    elf.extend(EHDR_SIZE.to_le_bytes()); // e_ehsize
+    elf.extend(PHDR_SIZE.to_le_bytes()); // e_phentsize
+    elf.extend(1u16.to_le_bytes()); // e_phnum
+    elf.extend(40u16.to_le_bytes()); // e_shentsize
+    elf.extend(0u16.to_le_bytes()); // e_shnum
+    elf.extend(0u16.to_le_bytes()); // e_shstrndx
+
+    elf.extend(1u32.to_le_bytes()); // p_type: PT_LOAD
+    elf.extend(((EHDR_SIZE + PHDR_SIZE) as u32).to_le_bytes()); // p_offset
+    elf.extend(entry.to_le_bytes()); // p_vaddr
+    elf.extend(entry.to_le_bytes()); // p_paddr
+    elf.extend((code.len() as u32).to_le_bytes()); // p_filesz
+    elf.extend((code.len() as u32).to_le_bytes()); // p_memsz
+    elf.extend(5u32.to_le_bytes()); // p_flags: R | X
+    elf.extend(4u32.to_le_bytes()); // p_align
+
+    elf.extend(code);
+    elf
+}
+
+#[test]
+fn embed_prebuilt_elf() {
+    let dir = tempdir().unwrap();
+    let pkg_dir = dir.path().canonicalize().unwrap().join("methods");
+    fs::create_dir_all(pkg_dir.join("src")).unwrap();
+    fs::write(
+        pkg_dir.join("Cargo.toml"),
+        r#"
+[package]
+name = "methods"
+version = "0.1.0"
+edition = "2021"
+
+[package.metadata.risc0]
+elfs = ["elfs/guest.elf"]
+"#,
+    )
+    .unwrap();
+    fs::write(pkg_dir.join("src/lib.rs"), "").unwrap();
+    fs::create_dir_all(pkg_dir.join("elfs")).unwrap();
+    let elf = minimal_elf();
+    fs::write(pkg_dir.join("elfs/guest.elf"), &elf).unwrap();
+
+    let out_dir = pkg_dir.join("target/release/build/methods-0123/out");
+    fs::create_dir_all(&out_dir).unwrap();
+    env::set_var("CARGO_MANIFEST_DIR", &pkg_dir);
+    env::set_var("OUT_DIR", &out_dir);
+    // Any attempt to fetch the standard library fails.
+    env::set_var("RISC0_STD_ARCHIVES", dir.path().join("missing"));
+
+    embed_methods();
+
+    // Nothing needed to build a guest was set up.
+    assert!(!out_dir.join("riscv32im-risc0-zkvm-elf.json").exists());
+
+    let program = Program::load_elf(&elf, MEM_SIZE as u32).unwrap();
+    let image_id: [u32; DIGEST_WORDS] = MemoryImage::new(&program, PAGE_SIZE as u32)
+        .unwrap()
+        .get_root()
+        .into();
+    let methods_rs = fs::read_to_string(out_dir.join("methods.rs")).unwrap();
+    assert!(methods_rs.contains(&format!("pub const GUEST_ELF: &[u8] = &{elf:?};")));
+    assert!(methods_rs.contains(&format!("pub const GUEST_ID: [u32; 8] = {image_id:?};")));
+}
@@ -0,0 +1,75 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Collects the files under `dir`, sorted so that the output is stable.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// Writes `templates.rs`, which embeds each directory of `templates` as a
+// template of the same name, so that the list of files never goes stale.
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let templates_dir = manifest_dir.join("templates");
+    println!("cargo:rerun-if-changed={}", templates_dir.display());
+
+    let mut templates: Vec<_> = fs::read_dir(&templates_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    templates.sort();
+
+    let mut out = String::from("pub(crate) const TEMPLATES: &[(&str, Template)] = &[\n");
+    for template_dir in templates {
+        let name = template_dir.file_name().unwrap().to_str().unwrap();
+        out.push_str(&format!("    ({name:?}, &[\n"));
+        let mut files = vec![];
+        collect_files(&template_dir, &mut files);
+        for path in files {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let rel_path = path.strip_prefix(&template_dir).unwrap().to_str().unwrap();
+            // Templates hold their manifests as `Cargo.toml.liquid`, as cargo
+            // would otherwise leave them out of the published crate. As with
+            // cargo-generate, the suffix is dropped when generating.
+            let rel_path = rel_path.strip_suffix(".liquid").unwrap_or(rel_path);
+            let rel_path = rel_path.replace('\\', "/");
+            out.push_str(&format!(
+                "        ({rel_path:?}, include_str!({:?})),\n",
+                path.to_str().unwrap()
+            ));
+        }
+        out.push_str("    ]),\n");
+    }
+    out.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("templates.rs"), out).unwrap();
+}
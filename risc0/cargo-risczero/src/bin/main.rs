@@ -27,5 +27,19 @@ fn main() {
         RisczeroCmd::New(new) => {
             new.run();
         }
+        RisczeroCmd::Build(build) => {
+            build.run();
+        }
+        RisczeroCmd::Run(run) => {
+            run.run();
+        }
+        RisczeroCmd::FetchStd(fetch_std) => {
+            fetch_std.run();
+        }
+        RisczeroCmd::VerifyBuild(verify_build) => {
+            if !verify_build.run() {
+                std::process::exit(1);
+            }
+        }
     }
 }
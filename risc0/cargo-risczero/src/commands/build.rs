@@ -0,0 +1,91 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use risc0_build::{build_guest, GuestOptions};
+
+#[derive(Parser)]
+/// `cargo risczero build`
+pub struct BuildCommand {
+    /// Directory of the guest package to build.
+    #[arg(default_value = ".")]
+    pub guest: PathBuf,
+
+    /// Features to build the guest with.
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Build the guest without standard library support.
+    #[arg(long)]
+    pub no_std: bool,
+
+    /// The cargo profile to build the guest with.
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Build with a pinned toolchain and normalized paths, as checked by
+    /// `cargo risczero verify-build`.
+    #[arg(long)]
+    pub reproducible: bool,
+
+    /// Directory to build the guest in. Defaults to `target/riscv-guest` in
+    /// the guest package.
+    #[arg(long)]
+    pub target_dir: Option<PathBuf>,
+}
+
+impl BuildCommand {
+    /// Execute this command
+    pub fn run(&self) {
+        let options = GuestOptions {
+            features: self.features.clone(),
+            std: !self.no_std,
+            reproducible: self.reproducible,
+            profile: self.profile.clone(),
+            target_dir: self.target_dir.clone(),
+            ..Default::default()
+        };
+        for method in build_guest(&self.guest, &options) {
+            println!(
+                "{}: {} {}",
+                method.name,
+                method.image_id,
+                method.elf_path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_build() {
+        let cmd = BuildCommand::parse_from([
+            "build",
+            "methods/guest",
+            "--features",
+            "foo,bar",
+            "--profile",
+            "dev",
+        ]);
+        assert_eq!(cmd.guest, PathBuf::from("methods/guest"));
+        assert_eq!(cmd.features, vec!["foo", "bar"]);
+        assert_eq!(cmd.profile.as_deref(), Some("dev"));
+        assert!(!cmd.no_std);
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(Parser)]
+/// `cargo risczero fetch-std`
+pub struct FetchStdCommand {
+    /// Local source of the archives
+    ///
+    /// Either a mirror directory containing the standard library zips, or a
+    /// bundle written by `--bundle`. If not given, the archives are
+    /// downloaded.
+    #[clap(value_parser, long)]
+    pub from: Option<PathBuf>,
+
+    /// Also write the archives to a single bundle file
+    ///
+    /// The bundle can be copied to machines without network access and used
+    /// with `--from`, or by setting `RISC0_STD_ARCHIVES` when building.
+    #[clap(value_parser, long)]
+    pub bundle: Option<PathBuf>,
+}
+
+impl FetchStdCommand {
+    /// Execute this command
+    pub fn run(&self) {
+        for (filename, sha256) in risc0_build::fetch_std(self.from.as_deref()) {
+            println!("{sha256}  {filename}");
+        }
+        if let Some(bundle) = self.bundle.as_ref() {
+            risc0_build::bundle_std(bundle);
+            println!("Wrote {}", bundle.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fetch_std() {
+        let cmd =
+            FetchStdCommand::parse_from(["fetch-std", "--from", "/mirror", "--bundle", "std.zip"]);
+        assert_eq!(cmd.from, Some(PathBuf::from("/mirror")));
+        assert_eq!(cmd.bundle, Some(PathBuf::from("std.zip")));
+    }
+}
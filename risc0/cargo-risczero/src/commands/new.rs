@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, process::Command};
 
 use cargo_generate::{GenerateArgs, TemplatePath, Vcs};
 use clap::Parser;
 use const_format::concatcp;
 
+use crate::template::{self, TEMPLATES};
+
 const RISC0_GH_REPO: &str = "https://github.com/risc0/risc0";
-const RISC0_TEMPLATE_DIR: &str = "templates/rust-starter";
+const RISC0_TEMPLATE_DIR: &str = "risc0/cargo-risczero/templates/rust-starter";
+/// Where the template lived in releases before [RISC0_TEMPLATE_DIR_SINCE].
+const RISC0_LEGACY_TEMPLATE_DIR: &str = "templates/rust-starter";
+/// The first release that ships the template at [RISC0_TEMPLATE_DIR].
+const RISC0_TEMPLATE_DIR_SINCE: (u64, u64, u64) = (0, 14, 0);
 const RISC0_DEFAULT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const RISC0_RELEASE_TAG: &str = concatcp!("v", RISC0_DEFAULT_VERSION);
 
@@ -30,17 +36,36 @@ pub struct NewCommand {
     #[arg()]
     pub name: String,
 
-    /// GH repository URL.
-    #[clap(value_parser, long, short, default_value = RISC0_GH_REPO)]
-    pub template: String,
+    /// GH repository URL or local path of the template.
+    ///
+    /// If this is not given, the template named by --embedded is generated
+    /// from the copy built into cargo-risczero, without network access.
+    #[clap(value_parser, long, short)]
+    pub template: Option<String>,
+
+    /// Name of the embedded template to generate from.
+    #[clap(
+        value_parser,
+        long,
+        default_value = "rust-starter",
+        conflicts_with = "template"
+    )]
+    pub embedded: String,
+
+    /// Fetch the template from the RISC Zero GitHub repository instead of
+    /// using the embedded copy.
+    #[clap(value_parser, long, conflicts_with = "template")]
+    pub remote: bool,
 
     /// Location of the template
     ///
     /// The subdirectory location of the template used for generating the new
     /// project. This path is relative to the base repository specified by
     /// --template
-    #[clap(value_parser, long, default_value = RISC0_TEMPLATE_DIR)]
-    pub templ_subdir: String,
+    ///
+    /// Defaults to the location of the template in the release named by --tag.
+    #[clap(value_parser, long)]
+    pub templ_subdir: Option<String>,
 
     /// template git tag.
     #[clap(value_parser, long, default_value = RISC0_RELEASE_TAG)]
@@ -90,22 +115,14 @@ impl NewCommand {
             std::env::current_dir().expect("Failed to fetch cwd")
         };
 
-        let mut template_path = TemplatePath {
-            auto_path: Some(self.template.clone()),
-            subfolder: Some(self.templ_subdir.clone()),
-            git: None,
-            branch: None,
-            path: None,
-            favorite: None,
-            tag: Some(self.tag.clone()),
-            test: false,
-        };
-
-        if !self.branch.is_empty() {
-            template_path.branch = Some(self.branch.clone());
-            template_path.tag = None;
+        match (&self.template, self.remote) {
+            (Some(template), _) => self.generate_remote(template, dest_dir),
+            (None, true) => self.generate_remote(RISC0_GH_REPO, dest_dir),
+            (None, false) => self.generate_embedded(dest_dir),
         }
+    }
 
+    fn template_variables(&self) -> Vec<(String, String)> {
         let risc0_version = std::env::var("CARGO_PKG_VERSION")
             .unwrap_or_else(|_| RISC0_DEFAULT_VERSION.to_string());
 
@@ -113,24 +130,97 @@ impl NewCommand {
         if let Some(branch) = self.use_git_branch.as_ref() {
             let spec =
                 format!("git = \"https://github.com/risc0/risc0.git\", branch = \"{branch}\"");
-            template_variables.push(format!("risc0_build={spec}"));
-            template_variables.push(format!("risc0_zkvm={spec}"));
+            template_variables.push(("risc0_build".to_string(), spec.clone()));
+            template_variables.push(("risc0_zkp".to_string(), spec.clone()));
+            template_variables.push(("risc0_zkvm".to_string(), spec));
         } else if let Some(path) = self.path.as_ref() {
             let path = path.to_str().unwrap();
             let build = format!("path = \"{path}/risc0/build\"");
+            let zkp = format!("path = \"{path}/risc0/zkp\"");
             let zkvm = format!("path = \"{path}/risc0/zkvm\"");
-            template_variables.push(format!("risc0_build={build}"));
-            template_variables.push(format!("risc0_zkvm={zkvm}"));
+            template_variables.push(("risc0_build".to_string(), build));
+            template_variables.push(("risc0_zkp".to_string(), zkp));
+            template_variables.push(("risc0_zkvm".to_string(), zkvm));
         } else {
             let spec = format!("version = \"{risc0_version}\"");
-            template_variables.push(format!("risc0_build={spec}"));
-            template_variables.push(format!("risc0_zkvm={spec}"));
+            template_variables.push(("risc0_build".to_string(), spec.clone()));
+            template_variables.push(("risc0_zkp".to_string(), spec.clone()));
+            template_variables.push(("risc0_zkvm".to_string(), spec));
         }
 
         if self.std {
-            template_variables.push("risc0_std=true".to_string());
-            template_variables.push("risc0_feature_std=, features = ['std']".to_string());
+            template_variables.push(("risc0_std".to_string(), "true".to_string()));
+            template_variables.push((
+                "risc0_feature_std".to_string(),
+                ", features = ['std']".to_string(),
+            ));
+        }
+        template_variables
+    }
+
+    fn generate_embedded(&self, dest_dir: PathBuf) {
+        let (_, files) = TEMPLATES
+            .iter()
+            .find(|(name, _)| *name == self.embedded)
+            .unwrap_or_else(|| panic!("Unknown embedded template: {}", self.embedded));
+
+        // cargo-generate defines these for every template.
+        let mut vars: HashMap<String, String> = self.template_variables().into_iter().collect();
+        vars.insert("project-name".to_string(), self.name.clone());
+        vars.insert("crate_name".to_string(), self.name.replace('-', "_"));
+
+        let proj_dir = dest_dir.join(&self.name);
+        template::generate(*files, &vars, &proj_dir);
+
+        if !self.no_git {
+            let status = Command::new("git")
+                .arg("init")
+                .arg("--quiet")
+                .arg(&proj_dir)
+                .status();
+            if !status.map_or(false, |status| status.success()) {
+                eprintln!("warning: failed to initialize a git repository");
+            }
         }
+        println!("Created {}", proj_dir.display());
+    }
+
+    /// The template subdirectory, falling back to where the template lived in
+    /// older release tags when no subdirectory is given.
+    fn templ_subdir(&self) -> String {
+        if let Some(subdir) = self.templ_subdir.as_ref() {
+            return subdir.clone();
+        }
+        match parse_release_tag(&self.tag) {
+            Some(version) if self.branch.is_empty() && version < RISC0_TEMPLATE_DIR_SINCE => {
+                RISC0_LEGACY_TEMPLATE_DIR.to_string()
+            }
+            _ => RISC0_TEMPLATE_DIR.to_string(),
+        }
+    }
+
+    fn generate_remote(&self, template: &str, dest_dir: PathBuf) {
+        let mut template_path = TemplatePath {
+            auto_path: Some(template.to_string()),
+            subfolder: Some(self.templ_subdir()),
+            git: None,
+            branch: None,
+            path: None,
+            favorite: None,
+            tag: Some(self.tag.clone()),
+            test: false,
+        };
+
+        if !self.branch.is_empty() {
+            template_path.branch = Some(self.branch.clone());
+            template_path.tag = None;
+        }
+
+        let template_variables = self
+            .template_variables()
+            .into_iter()
+            .map(|(key, val)| format!("{key}={val}"))
+            .collect();
 
         cargo_generate::generate(GenerateArgs {
             template_path,
@@ -161,6 +251,21 @@ impl NewCommand {
     }
 }
 
+/// Parses a release tag of the form `vMAJOR.MINOR.PATCH`.
+fn parse_release_tag(tag: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = tag.strip_prefix('v')?.splitn(3, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    // Ignore any pre-release or build suffix, e.g. `v0.14.0-rc.1`.
+    let patch = parts.next()?;
+    let patch = patch
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()?;
+    Some((major, minor, patch))
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -177,9 +282,7 @@ mod tests {
         let tmpdir = tempdir().expect("Failed to create tempdir");
         let manifest_path =
             std::env::var("CARGO_MANIFEST_DIR").expect("Missing CARGO_MANIFEST_DIR var");
-        // NOTE: cargo run and cargo test have a slightly different idea of what the
-        // manifest_dir is. https://github.com/rust-lang/cargo/issues/3946
-        let template_path = Path::new(&manifest_path).join("../../");
+        let template_path = Path::new(&manifest_path).join("templates/rust-starter");
         (tmpdir, template_path, "my_project")
     }
 
@@ -201,6 +304,40 @@ mod tests {
         assert_eq!(new.name, "my_project");
     }
 
+    #[test]
+    fn remote_templ_subdir() {
+        let new = NewCommand::parse_from(["new", "--remote", "my_project"]);
+        assert_eq!(new.templ_subdir(), RISC0_TEMPLATE_DIR);
+
+        let new = NewCommand::parse_from(["new", "--remote", "--tag", "v0.13.0", "my_project"]);
+        assert_eq!(new.templ_subdir(), RISC0_LEGACY_TEMPLATE_DIR);
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--remote",
+            "--tag",
+            "v0.13.0",
+            "--branch",
+            "main",
+            "my_project",
+        ]);
+        assert_eq!(new.templ_subdir(), RISC0_TEMPLATE_DIR);
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--remote",
+            "--tag",
+            "v0.13.0",
+            "--templ-subdir",
+            "custom",
+            "my_project",
+        ]);
+        assert_eq!(new.templ_subdir(), "custom");
+
+        assert_eq!(parse_release_tag("v0.14.0-rc.1"), Some((0, 14, 0)));
+        assert_eq!(parse_release_tag("main"), None);
+    }
+
     #[test]
     fn basic_generate() {
         let (tmpdir, template_path, proj_name) = make_test_env();
@@ -208,9 +345,7 @@ mod tests {
         let new = NewCommand::parse_from([
             "new",
             "--template",
-            &template_path
-                .join("templates/rust-starter")
-                .to_string_lossy(),
+            &template_path.to_string_lossy(),
             "--templ-subdir",
             "",
             "--dest",
@@ -235,6 +370,68 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn generate_embedded() {
+        let (tmpdir, _, proj_name) = make_test_env();
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--dest",
+            &tmpdir.path().to_string_lossy(),
+            "--no-git",
+            proj_name,
+        ]);
+
+        new.run();
+
+        let proj_path = tmpdir.path().join(proj_name);
+
+        assert!(!proj_path.join(".git").exists());
+        assert!(find_in_file(
+            &format!("risc0-zkvm = {{ version = \"{RISC0_DEFAULT_VERSION}\" }}"),
+            &proj_path.join("host/Cargo.toml")
+        ));
+        assert!(find_in_file(
+            "#![no_std]",
+            &proj_path.join("methods/guest/src/main.rs")
+        ));
+        assert!(!find_in_file("{{", &proj_path.join("methods/Cargo.toml")));
+    }
+
+    #[test]
+    fn generate_embedded_bonsai() {
+        let (tmpdir, _, proj_name) = make_test_env();
+
+        let new = NewCommand::parse_from([
+            "new",
+            "--embedded",
+            "bonsai",
+            "--dest",
+            &tmpdir.path().to_string_lossy(),
+            "--no-git",
+            "--path",
+            "/risc0",
+            proj_name,
+        ]);
+
+        new.run();
+
+        let proj_path = tmpdir.path().join(proj_name);
+
+        assert!(!proj_path.join("Cargo.toml.liquid").exists());
+        assert!(find_in_file(
+            "risc0-zkp = { path = \"/risc0/risc0/zkp\" }",
+            &proj_path.join("Cargo.toml")
+        ));
+        assert!(find_in_file(
+            "risc0-zkvm = { path = \"/risc0/risc0/zkvm\", default-features = false }",
+            &proj_path.join("methods/guest/Cargo.toml")
+        ));
+        assert!(proj_path
+            .join("contracts/contracts/HelloBonsai.sol")
+            .exists());
+    }
+
     #[test]
     fn generate_no_git_branch() {
         let (tmpdir, template_path, proj_name) = make_test_env();
@@ -242,9 +439,7 @@ mod tests {
         let new = NewCommand::parse_from([
             "new",
             "--template",
-            &template_path
-                .join("templates/rust-starter")
-                .to_string_lossy(),
+            &template_path.to_string_lossy(),
             "--templ-subdir",
             "",
             "--dest",
@@ -274,9 +469,7 @@ mod tests {
         let new = NewCommand::parse_from([
             "new",
             "--template",
-            &template_path
-                .join("templates/rust-starter")
-                .to_string_lossy(),
+            &template_path.to_string_lossy(),
             "--templ-subdir",
             "",
             "--dest",
@@ -0,0 +1,123 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use risc0_zkvm::{Executor, ExecutorEnv, MemoryImage, Program, MEM_SIZE, PAGE_SIZE};
+
+#[derive(Parser)]
+/// `cargo risczero run`
+pub struct RunCommand {
+    /// Path to the guest ELF.
+    #[arg()]
+    pub elf: PathBuf,
+
+    /// File whose contents are passed to the guest on stdin.
+    #[arg(long)]
+    pub stdin: Option<PathBuf>,
+
+    /// Environment variables for the guest, as `KEY=VALUE`.
+    #[arg(long = "env", value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+
+    /// Prove the execution and verify the resulting receipt.
+    #[arg(long)]
+    pub prove: bool,
+}
+
+fn parse_env_var(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, val)| (key.to_string(), val.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, found `{arg}`"))
+}
+
+impl RunCommand {
+    /// Execute this command
+    pub fn run(&self) {
+        let elf = fs::read(&self.elf).expect("Failed to read guest ELF");
+        let stdin = match &self.stdin {
+            Some(path) => fs::read(path).expect("Failed to read stdin file"),
+            None => vec![],
+        };
+
+        let mut builder = ExecutorEnv::builder();
+        builder.stdin(stdin.as_slice());
+        for (key, val) in self.env.iter() {
+            builder.env_var(key, val);
+        }
+        let env = builder.build();
+
+        let mut exec = Executor::from_elf(env, &elf).expect("Failed to load guest ELF");
+        let session = exec.run().expect("Guest execution failed");
+        let segments = session.resolve().expect("Failed to resolve segments");
+
+        println!("exit code: {:?}", session.exit_code);
+        println!("journal: {}", hex(&session.journal));
+        let mut insn_cycles = 0;
+        let mut total_cycles = 0;
+        for segment in segments.iter() {
+            println!(
+                "segment {}: {} instruction cycles, 2^{} total cycles",
+                segment.index, segment.insn_cycles, segment.po2
+            );
+            insn_cycles += segment.insn_cycles;
+            total_cycles += 1 << segment.po2;
+        }
+        println!("{insn_cycles} instruction cycles, {total_cycles} total cycles");
+
+        if self.prove {
+            let program = Program::load_elf(&elf, MEM_SIZE as u32).unwrap();
+            let image_id = MemoryImage::new(&program, PAGE_SIZE as u32)
+                .unwrap()
+                .get_root();
+            let receipt = session.prove().expect("Proving failed");
+            receipt
+                .verify(image_id)
+                .expect("Receipt verification failed");
+            println!("verified receipt for image ID {image_id}");
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_run() {
+        let cmd = RunCommand::parse_from([
+            "run",
+            "guest.elf",
+            "--stdin",
+            "input.bin",
+            "--env",
+            "RUST_LOG=debug",
+            "--prove",
+        ]);
+        assert_eq!(cmd.elf, PathBuf::from("guest.elf"));
+        assert_eq!(cmd.stdin, Some(PathBuf::from("input.bin")));
+        assert_eq!(cmd.env, vec![("RUST_LOG".into(), "debug".into())]);
+        assert!(cmd.prove);
+    }
+
+    #[test]
+    fn reject_malformed_env() {
+        assert!(RunCommand::try_parse_from(["run", "guest.elf", "--env", "RUST_LOG"]).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use risc0_build::{rebuild_guest, BuildManifest};
+
+#[derive(Parser)]
+/// `cargo risczero verify-build`
+pub struct VerifyBuildCommand {
+    /// Path to the build manifest
+    ///
+    /// The `methods.manifest.json` written by `risc0_build::embed_methods`
+    /// next to `methods.rs`.
+    #[arg()]
+    pub manifest: PathBuf,
+
+    /// Directory of the guest package to rebuild.
+    #[arg()]
+    pub guest: PathBuf,
+}
+
+impl VerifyBuildCommand {
+    /// Execute this command, returning whether every image ID matched.
+    pub fn run(&self) -> bool {
+        let manifests: Vec<BuildManifest> = serde_json::from_slice(
+            &fs::read(&self.manifest).expect("Failed to read build manifest"),
+        )
+        .expect("Failed to parse build manifest");
+
+        let guest_manifest = cargo_metadata::MetadataCommand::new()
+            .manifest_path(self.guest.join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .expect("Failed to read guest package");
+        let guest_name = &guest_manifest.root_package().unwrap().name;
+        let expected = manifests
+            .iter()
+            .find(|manifest| &manifest.package == guest_name)
+            .unwrap_or_else(|| panic!("{guest_name} is not in the build manifest"));
+        if !expected.reproducible {
+            eprintln!("warning: {guest_name} was not built in reproducible mode");
+        }
+
+        let actual = rebuild_guest(&self.guest, expected);
+        if actual.toolchain != expected.toolchain {
+            eprintln!(
+                "warning: toolchain {} differs from {}",
+                actual.toolchain, expected.toolchain
+            );
+        }
+        if actual.std_source != expected.std_source {
+            eprintln!(
+                "warning: standard library {} differs from {}",
+                actual.std_source, expected.std_source
+            );
+        }
+
+        let mut ok = true;
+        for method in expected.methods.iter() {
+            match actual.methods.iter().find(|m| m.name == method.name) {
+                Some(rebuilt) if rebuilt.image_id == method.image_id => {
+                    println!("{}: OK {}", method.name, method.image_id);
+                }
+                Some(rebuilt) => {
+                    println!(
+                        "{}: MISMATCH expected {}, rebuilt {}",
+                        method.name, method.image_id, rebuilt.image_id
+                    );
+                    ok = false;
+                }
+                None => {
+                    println!("{}: MISSING from rebuilt guest", method.name);
+                    ok = false;
+                }
+            }
+        }
+        ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_verify_build() {
+        let cmd = VerifyBuildCommand::parse_from([
+            "verify-build",
+            "methods.manifest.json",
+            "methods/guest",
+        ]);
+        assert_eq!(cmd.manifest, PathBuf::from("methods.manifest.json"));
+        assert_eq!(cmd.guest, PathBuf::from("methods/guest"));
+    }
+}
@@ -17,14 +17,27 @@
 
 use clap::{Parser, Subcommand};
 
-use crate::commands::new::NewCommand;
+use crate::commands::{
+    build::BuildCommand, fetch_std::FetchStdCommand, new::NewCommand, run::RunCommand,
+    verify_build::VerifyBuildCommand,
+};
 
 /// Implementations of the commands
 pub mod commands {
+    /// Build a guest package and print its image IDs
+    pub mod build;
+    /// Pre-fetch the standard library sources for offline guest builds
+    pub mod fetch_std;
     /// Create a new RISC Zero project
     pub mod new;
+    /// Execute a guest ELF, and optionally prove it
+    pub mod run;
+    /// Check that a guest rebuilds to the recorded image IDs
+    pub mod verify_build;
 }
 
+mod template;
+
 #[derive(Parser)]
 #[command(name = "cargo", bin_name = "cargo")]
 /// Main cargo command
@@ -47,6 +60,19 @@ pub struct Risczero {
 pub enum RisczeroCmd {
     /// Creates a new risczero starter project.
     New(NewCommand),
+
+    /// Builds a guest package and prints the image ID of each method.
+    Build(BuildCommand),
+
+    /// Executes a guest ELF and prints its journal and cycle counts.
+    Run(RunCommand),
+
+    /// Populates the local cache with the guest standard library sources.
+    FetchStd(FetchStdCommand),
+
+    /// Rebuilds a guest reproducibly and checks its image IDs against a build
+    /// manifest.
+    VerifyBuild(VerifyBuildCommand),
 }
 
 #[cfg(test)]
@@ -0,0 +1,178 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Project templates embedded in the binary, so that `cargo risczero new`
+//! works without network access. Every directory in `templates` is embedded
+//! by the build script as a template of the same name.
+//!
+//! Templates are written for cargo-generate, so that they can also be used
+//! remotely. [render] supports the subset of its Liquid syntax that they use:
+//! `{{ var }}`, `{% if var %}` and `{% unless var %}` blocks, and `-` for
+//! trimming whitespace around a tag.
+
+use std::{collections::HashMap, fs, path::Path};
+
+/// The files of a template, as pairs of relative path and contents.
+pub(crate) type Template = &'static [(&'static str, &'static str)];
+
+// The embedded templates, by name.
+include!(concat!(env!("OUT_DIR"), "/templates.rs"));
+
+/// Render each file of `template` with `vars` into `dest`, which must not
+/// already exist.
+pub(crate) fn generate(template: Template, vars: &HashMap<String, String>, dest: &Path) {
+    if dest.exists() {
+        panic!("Destination already exists: {}", dest.display());
+    }
+    for (path, contents) in template.iter() {
+        let path = dest.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, render(contents, vars))
+            .unwrap_or_else(|err| panic!("Failed to write {}: {err}", path.display()));
+    }
+}
+
+/// Render a single template file. Variables that are not in `vars` are empty
+/// and falsy, as are those set to `false`.
+pub(crate) fn render(template: &str, vars: &HashMap<String, String>) -> String {
+    let truthy = |name: &str| {
+        vars.get(name)
+            .map_or(false, |val| !val.is_empty() && val != "false")
+    };
+
+    let mut out = String::new();
+    // Whether each enclosing block is rendered.
+    let mut active = vec![true];
+    let mut trim_start = false;
+    let mut rest = template;
+    loop {
+        let next = [rest.find("{{"), rest.find("{%")]
+            .into_iter()
+            .flatten()
+            .min();
+        let (text, tag) = match next {
+            Some(pos) => (&rest[..pos], Some(&rest[pos..])),
+            None => (rest, None),
+        };
+
+        let mut text = text;
+        if trim_start {
+            text = text.trim_start();
+        }
+        let tag = tag.map(|tag| {
+            let close = if tag.starts_with("{{") { "}}" } else { "%}" };
+            let end = tag
+                .find(close)
+                .unwrap_or_else(|| panic!("Unterminated template tag: {tag}"));
+            (&tag[..2], &tag[2..end], &tag[end + 2..])
+        });
+        if let Some((_, inner, _)) = tag {
+            if inner.starts_with('-') {
+                text = text.trim_end();
+            }
+        }
+        if active.iter().all(|active| *active) {
+            out.push_str(text);
+        }
+
+        let (open, inner, after) = match tag {
+            Some(tag) => tag,
+            None => break,
+        };
+        trim_start = inner.ends_with('-');
+        let inner = inner.trim_matches('-').trim();
+        if open == "{{" {
+            if active.iter().all(|active| *active) {
+                out.push_str(vars.get(inner).map_or("", String::as_str));
+            }
+        } else {
+            let mut words = inner.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("if"), Some(name)) => active.push(truthy(name)),
+                (Some("unless"), Some(name)) => active.push(!truthy(name)),
+                (Some("endif" | "endunless"), None) if active.len() > 1 => {
+                    active.pop();
+                }
+                _ => panic!("Unsupported template tag: {{%{inner}%}}"),
+            }
+        }
+        rest = after;
+    }
+    assert_eq!(active.len(), 1, "Unterminated template block");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, val)| (key.to_string(), val.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitute() {
+        let vars = vars(&[("risc0_zkvm", "version = \"0.14.0\"")]);
+        assert_eq!(
+            render("risc0-zkvm = { {{ risc0_zkvm }}{{ missing }} }", &vars),
+            "risc0-zkvm = { version = \"0.14.0\" }"
+        );
+    }
+
+    #[test]
+    fn unless_block() {
+        let template = "a\n{% unless risc0_std -%}\nb\n{% endunless %}\nc";
+        assert_eq!(render(template, &vars(&[])), "a\nb\n\nc");
+        assert_eq!(render(template, &vars(&[("risc0_std", "true")])), "a\n\nc");
+    }
+
+    #[test]
+    fn trim_before() {
+        let template = "[dependencies]\n{%- unless risc0_std %}\n# comment\n{%- endunless %}\nfoo";
+        assert_eq!(
+            render(template, &vars(&[])),
+            "[dependencies]\n# comment\nfoo"
+        );
+        assert_eq!(
+            render(template, &vars(&[("risc0_std", "true")])),
+            "[dependencies]\nfoo"
+        );
+    }
+
+    #[test]
+    fn templates_render() {
+        let vars = vars(&[
+            ("risc0_build", "version = \"0.14.0\""),
+            ("risc0_zkp", "version = \"0.14.0\""),
+            ("risc0_zkvm", "version = \"0.14.0\""),
+        ]);
+        let names: Vec<_> = TEMPLATES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["bonsai", "rust-starter"]);
+        for (name, files) in TEMPLATES.iter() {
+            assert!(
+                files.iter().any(|(path, _)| *path == "Cargo.toml"),
+                "{name}"
+            );
+            for (path, contents) in files.iter() {
+                assert!(!path.ends_with(".liquid"), "{name}/{path}");
+                let rendered = render(contents, &vars);
+                assert!(!rendered.contains("{{"), "{name}/{path}");
+                assert!(!rendered.contains("{%"), "{name}/{path}");
+            }
+        }
+    }
+}
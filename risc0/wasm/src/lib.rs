@@ -0,0 +1,129 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [SessionReceipt] verifier for WebAssembly hosts such as browsers and edge
+//! workers.
+//!
+//! The entry point is [verify_receipt], which is exported to JavaScript by
+//! `wasm-bindgen`:
+//!
+//! ```js
+//! import { verify_receipt } from "risc0_wasm";
+//!
+//! try {
+//!   const journal = verify_receipt(receiptBytes, imageIdBytes);
+//! } catch (err) {
+//!   console.log(err.kind, err.message);
+//! }
+//! ```
+
+use risc0_zkp::verify::VerificationError;
+use risc0_zkvm::{
+    serde::from_slice,
+    sha::{Digest, DIGEST_BYTES},
+    SessionReceipt,
+};
+use wasm_bindgen::prelude::*;
+
+/// The category of a [VerifyError].
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+    /// The receipt bytes could not be decoded as a [SessionReceipt].
+    MalformedReceipt,
+
+    /// The image ID was not [DIGEST_BYTES] long.
+    MalformedImageId,
+
+    /// The receipt is for a different image ID.
+    ImageIdMismatch,
+
+    /// The receipt did not pass verification.
+    InvalidReceipt,
+}
+
+/// The error returned by [verify_receipt].
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct VerifyError {
+    kind: VerifyErrorKind,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl VerifyError {
+    /// The category of this error.
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> VerifyErrorKind {
+        self.kind
+    }
+
+    /// A human readable description of this error.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl VerifyError {
+    fn new(kind: VerifyErrorKind, message: impl ToString) -> Self {
+        Self {
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<VerificationError> for VerifyError {
+    fn from(err: VerificationError) -> Self {
+        let kind = match err {
+            VerificationError::ReceiptFormatError => VerifyErrorKind::MalformedReceipt,
+            VerificationError::ImageVerificationError => VerifyErrorKind::ImageIdMismatch,
+            _ => VerifyErrorKind::InvalidReceipt,
+        };
+        Self::new(kind, err)
+    }
+}
+
+/// Verify a serialized [SessionReceipt] against `image_id`, and return its
+/// journal.
+///
+/// `receipt` holds the words produced by
+/// [risc0_zkvm::serde::to_vec] in little-endian byte order, and `image_id` is
+/// the [DIGEST_BYTES] bytes of the image ID.
+#[wasm_bindgen]
+pub fn verify_receipt(receipt: &[u8], image_id: &[u8]) -> Result<Vec<u8>, VerifyError> {
+    if receipt.len() % 4 != 0 {
+        return Err(VerifyError::new(
+            VerifyErrorKind::MalformedReceipt,
+            "receipt length is not a multiple of 4 bytes",
+        ));
+    }
+    let words: Vec<u32> = receipt
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    let receipt: SessionReceipt = from_slice(&words)
+        .map_err(|err| VerifyError::new(VerifyErrorKind::MalformedReceipt, err))?;
+
+    let image_id = Digest::try_from(image_id).map_err(|_| {
+        VerifyError::new(
+            VerifyErrorKind::MalformedImageId,
+            format!("image ID must be {DIGEST_BYTES} bytes"),
+        )
+    })?;
+
+    receipt.verify(image_id)?;
+    Ok(receipt.journal)
+}
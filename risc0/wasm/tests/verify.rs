@@ -0,0 +1,71 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Proves a real receipt on the host and checks it with the same entry point
+// that is exported to JavaScript. Proving is not available on wasm32, so the
+// receipt is also written to the target directory, from which `web.rs` embeds
+// it to verify it under a wasm runtime.
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{fs, path::Path};
+
+use risc0_wasm::{verify_receipt, VerifyErrorKind};
+use risc0_zkvm::{
+    serde::to_vec,
+    sha::{Digest, DIGEST_BYTES},
+    Executor, ExecutorEnv,
+};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+
+fn prove_receipt() -> (Vec<u8>, Vec<u8>) {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let env = ExecutorEnv::builder().add_input(&input).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let receipt = exec.run().unwrap().prove().unwrap();
+    let bytes = to_vec(&receipt)
+        .unwrap()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    (bytes, receipt.journal)
+}
+
+// Writes the fixtures embedded by `web.rs`.
+fn write_fixtures(receipt: &[u8], image_id: &Digest, journal: &[u8]) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("receipt");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("receipt.bin"), receipt).unwrap();
+    fs::write(dir.join("image_id.bin"), image_id.as_bytes()).unwrap();
+    fs::write(dir.join("journal.bin"), journal).unwrap();
+}
+
+#[test]
+fn verify() {
+    let (receipt, journal) = prove_receipt();
+    let image_id = Digest::from(MULTI_TEST_ID);
+    write_fixtures(&receipt, &image_id, &journal);
+    assert_eq!(
+        verify_receipt(&receipt, image_id.as_bytes()).unwrap(),
+        journal
+    );
+
+    let err = verify_receipt(&receipt, &[0; DIGEST_BYTES]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::ImageIdMismatch);
+
+    let err = verify_receipt(&receipt, &image_id.as_bytes()[1..]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::MalformedImageId);
+
+    let err = verify_receipt(&receipt[..receipt.len() - 1], image_id.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::MalformedReceipt);
+}
@@ -0,0 +1,68 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Run with `wasm-bindgen-test-runner` as the cargo runner for wasm32, which
+// executes the tests under a local Node.js.
+//
+// The receipt verified here is proven and written to the target directory by
+// the host tests in `verify.rs`, so run `cargo test` on the host first.
+#![cfg(target_arch = "wasm32")]
+
+use risc0_wasm::{verify_receipt, VerifyErrorKind};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const RECEIPT: &[u8] = include_bytes!(concat!(env!("CARGO_TARGET_TMPDIR"), "/receipt/receipt.bin"));
+const IMAGE_ID: &[u8] = include_bytes!(concat!(
+    env!("CARGO_TARGET_TMPDIR"),
+    "/receipt/image_id.bin"
+));
+const JOURNAL: &[u8] = include_bytes!(concat!(env!("CARGO_TARGET_TMPDIR"), "/receipt/journal.bin"));
+
+#[wasm_bindgen_test]
+fn verify() {
+    assert_eq!(verify_receipt(RECEIPT, IMAGE_ID).unwrap(), JOURNAL);
+
+    let err = verify_receipt(RECEIPT, &[0; 32]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::ImageIdMismatch);
+}
+
+#[wasm_bindgen_test]
+fn tampered_receipt() {
+    // Flip a bit in the middle of the receipt, which lands in a seal.
+    let mut receipt = RECEIPT.to_vec();
+    let index = receipt.len() / 2;
+    receipt[index] ^= 1;
+    assert!(verify_receipt(&receipt, IMAGE_ID).is_err());
+}
+
+#[wasm_bindgen_test]
+fn empty_receipt() {
+    // A receipt without any segments does not prove anything.
+    let receipt = [0u8; 8];
+    let err = verify_receipt(&receipt, &[0; 32]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::MalformedReceipt);
+}
+
+#[wasm_bindgen_test]
+fn truncated_receipt() {
+    let err = verify_receipt(&[0; 3], &[0; 32]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::MalformedReceipt);
+    assert!(!err.message().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn malformed_image_id() {
+    let err = verify_receipt(&[0; 8], &[0; 31]).unwrap_err();
+    assert_eq!(err.kind(), VerifyErrorKind::MalformedImageId);
+}
@@ -26,6 +26,7 @@ pub mod core;
 pub mod hal;
 pub mod layout;
 mod merkle;
+mod params;
 #[cfg(feature = "prove")]
 pub mod prove;
 pub mod taps;
@@ -42,14 +43,18 @@ pub mod hal {
 
 pub use risc0_core::field;
 
+pub use self::params::{ProofParams, SealVersion};
+
 pub const MIN_CYCLES_PO2: usize = 11;
 pub const MIN_CYCLES: usize = 1 << MIN_CYCLES_PO2; // 1K
 pub const MAX_CYCLES_PO2: usize = 24;
 pub const MAX_CYCLES: usize = 1 << MAX_CYCLES_PO2; // 16M
 
-/// 50 FRI queries gives ~100 bits of conjectured security
-pub const QUERIES: usize = 50;
-pub const ZK_CYCLES: usize = QUERIES;
+/// The number of cycles of random padding at the end of the trace. Each FRI
+/// query reveals one evaluation of the trace, so no [ProofParams] may make more
+/// queries than this. Changing it changes the code trace, and so every control
+/// ID.
+pub const ZK_CYCLES: usize = 50;
 pub const MIN_PO2: usize = core::log2_ceil(1 + ZK_CYCLES);
 
 /// Inverse of Reed-Solomon Expansion Rate
@@ -57,5 +62,3 @@ pub const INV_RATE: usize = 4;
 /// FRI folding factor is 2 ^ FRI_FOLD_PO2
 const FRI_FOLD_PO2: usize = 4;
 pub const FRI_FOLD: usize = 1 << FRI_FOLD_PO2;
-/// FRI continues until the degree of the FRI polynomial reaches FRI_MIN_DEGREE
-const FRI_MIN_DEGREE: usize = 256;
@@ -0,0 +1,265 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parameters of a seal that are chosen by the prover and recorded in the
+//! seal's header word.
+//!
+//! The header word follows the circuit outputs at the start of the seal and
+//! is laid out as:
+//! * bits 0..8: the po2 of the number of cycles
+//! * bits 8..16: the [ProofParams] preset
+//! * bits 16..32: the [SealVersion]
+//!
+//! Seals from before these fields existed contain only the po2, and so decode
+//! as [SealVersion::Paths] with [ProofParams::DEFAULT].
+
+use risc0_core::field::Field;
+
+use crate::{
+    core::{
+        digest::{Digest, DIGEST_WORDS},
+        hash::HashFn,
+        log2_ceil,
+    },
+    verify::VerificationError,
+    INV_RATE, ZK_CYCLES,
+};
+
+const PO2_BITS: u32 = 8;
+const PARAMS_SHIFT: u32 = PO2_BITS;
+const PARAMS_BITS: u32 = 8;
+const SEAL_VERSION_SHIFT: u32 = PARAMS_SHIFT + PARAMS_BITS;
+
+/// The encoding used for the query openings in a seal.
+///
+/// The version is recorded in the header word of the seal, so a verifier can
+/// accept seals of any supported version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SealVersion {
+    /// Each FRI query opens every Merkle tree with a full authentication path.
+    Paths,
+
+    /// The openings of all FRI queries into each Merkle tree are batched into
+    /// a single multiproof, in which shared siblings are only written once.
+    #[default]
+    Multiproof,
+}
+
+impl SealVersion {
+    fn encode(self) -> u32 {
+        match self {
+            SealVersion::Paths => 0,
+            SealVersion::Multiproof => 1,
+        }
+    }
+
+    fn decode(value: u32) -> Result<Self, VerificationError> {
+        match value {
+            0 => Ok(SealVersion::Paths),
+            1 => Ok(SealVersion::Multiproof),
+            _ => Err(VerificationError::ReceiptFormatError),
+        }
+    }
+}
+
+/// The parameters of the STARK protocol that trade seal size and proving time
+/// against security.
+///
+/// Only the presets defined here can be used, so that a verifier never
+/// accepts a seal made with fewer queries than [ProofParams::FAST]. The
+/// expansion rate ([INV_RATE]) and the FRI folding factor
+/// ([FRI_FOLD](crate::FRI_FOLD)) are not included, as both are fixed by the
+/// circuit and the HAL kernels.
+///
+/// No preset makes more than [ZK_CYCLES] queries, since each query reveals a
+/// cycle of the trace, and raising [ZK_CYCLES] would change every control ID.
+/// Presets beyond ~100 bits of security instead add a proof of work before
+/// the queries are drawn, which costs the prover about `2^pow_bits` hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofParams {
+    id: u32,
+    queries: usize,
+    fri_min_degree: usize,
+    pow_bits: usize,
+}
+
+impl ProofParams {
+    /// ~80 bits of conjectured security, for the smallest and fastest seals.
+    pub const FAST: Self = Self {
+        id: 1,
+        queries: 40,
+        fri_min_degree: 256,
+        pow_bits: 0,
+    };
+
+    /// ~100 bits of conjectured security.
+    pub const DEFAULT: Self = Self {
+        id: 0,
+        queries: 50,
+        fri_min_degree: 256,
+        pow_bits: 0,
+    };
+
+    /// ~128 bits of conjectured security, with the queries of
+    /// [ProofParams::DEFAULT] and a 28-bit proof of work. Finding the proof of
+    /// work takes around 2^28 hashes of the hash suite, so this is best used
+    /// with SHA-256.
+    pub const CONSERVATIVE: Self = Self {
+        id: 2,
+        queries: 50,
+        fri_min_degree: 256,
+        pow_bits: 28,
+    };
+
+    const PRESETS: [Self; 3] = [Self::DEFAULT, Self::FAST, Self::CONSERVATIVE];
+
+    /// The number of FRI queries.
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+
+    /// FRI continues until the degree of the FRI polynomial reaches this
+    /// value.
+    pub fn fri_min_degree(&self) -> usize {
+        self.fri_min_degree
+    }
+
+    /// The number of leading zero bits required of the proof of work, or zero
+    /// if there is none.
+    pub fn pow_bits(&self) -> usize {
+        self.pow_bits
+    }
+
+    /// The conjectured security of these parameters, in bits. Each query
+    /// contributes log2([INV_RATE]) bits, and the proof of work adds
+    /// [ProofParams::pow_bits].
+    pub fn security_bits(&self) -> usize {
+        self.queries * log2_ceil(INV_RATE) + self.pow_bits
+    }
+
+    /// Returns the parameters recorded in the header word of a seal, which
+    /// follows the circuit outputs.
+    pub fn from_seal_header(word: u32) -> Result<Self, VerificationError> {
+        decode_seal_header(word).map(|(_, params, _)| params)
+    }
+
+    fn decode(value: u32) -> Result<Self, VerificationError> {
+        Self::PRESETS
+            .into_iter()
+            .find(|params| params.id == value)
+            .ok_or(VerificationError::ReceiptFormatError)
+    }
+}
+
+impl Default for ProofParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Builds the challenge for the proof of work from words drawn from the
+/// transcript.
+pub(crate) fn pow_challenge(mut random_bits: impl FnMut(usize) -> u32) -> Digest {
+    Digest::new(core::array::from_fn(|_| {
+        random_bits(16) | random_bits(16) << 16
+    }))
+}
+
+/// Returns whether `nonce` solves the proof of work for `challenge`, i.e.
+/// whether their hash starts with `bits` zero bits.
+pub(crate) fn pow_check<F: Field, H: HashFn<F>>(
+    challenge: &Digest,
+    nonce: u32,
+    bits: usize,
+) -> bool {
+    let mut words = [0; DIGEST_WORDS];
+    words[0] = nonce;
+    let digest = H::hash_pair(challenge, &Digest::new(words));
+    digest.as_words()[0].leading_zeros() as usize >= bits
+}
+
+/// Pack `version`, `params` and `po2` into the header word of a seal.
+pub(crate) fn encode_seal_header(version: SealVersion, params: &ProofParams, po2: u32) -> u32 {
+    assert!(po2 < 1 << PO2_BITS);
+    (version.encode() << SEAL_VERSION_SHIFT) | (params.id << PARAMS_SHIFT) | po2
+}
+
+/// Unpack the [SealVersion], [ProofParams] and po2 from the header word of a
+/// seal.
+pub(crate) fn decode_seal_header(
+    word: u32,
+) -> Result<(SealVersion, ProofParams, u32), VerificationError> {
+    let version = SealVersion::decode(word >> SEAL_VERSION_SHIFT)?;
+    let params = ProofParams::decode((word >> PARAMS_SHIFT) & ((1 << PARAMS_BITS) - 1))?;
+    Ok((version, params, word & ((1 << PO2_BITS) - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_header_round_trip() {
+        for version in [SealVersion::Paths, SealVersion::Multiproof] {
+            for params in ProofParams::PRESETS {
+                let word = encode_seal_header(version, &params, 20);
+                assert_eq!(decode_seal_header(word).unwrap(), (version, params, 20));
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_seal_header() {
+        assert_eq!(
+            decode_seal_header(20).unwrap(),
+            (SealVersion::Paths, ProofParams::DEFAULT, 20)
+        );
+    }
+
+    #[test]
+    fn unknown_params() {
+        assert!(decode_seal_header(0xff << PARAMS_SHIFT).is_err());
+    }
+
+    #[test]
+    fn security_bits() {
+        assert_eq!(ProofParams::FAST.security_bits(), 80);
+        assert_eq!(ProofParams::DEFAULT.security_bits(), 100);
+        assert_eq!(ProofParams::CONSERVATIVE.security_bits(), 128);
+    }
+
+    #[test]
+    fn queries_within_zk_cycles() {
+        for params in ProofParams::PRESETS {
+            assert!(params.queries() <= ZK_CYCLES);
+            assert!(params.pow_bits() < 32);
+        }
+    }
+
+    #[test]
+    fn pow() {
+        use risc0_core::field::baby_bear::BabyBear;
+
+        use crate::core::hash::sha::{cpu::Impl, Sha256HashFn};
+        type Hash = Sha256HashFn<Impl>;
+
+        let challenge = Digest::new([1, 2, 3, 4, 5, 6, 7, 8]);
+        let nonce = (0..)
+            .find(|nonce| pow_check::<BabyBear, Hash>(&challenge, *nonce, 8))
+            .unwrap();
+        assert!(pow_check::<BabyBear, Hash>(&challenge, nonce, 8));
+        assert!((0..nonce).all(|nonce| !pow_check::<BabyBear, Hash>(&challenge, nonce, 8)));
+        assert!(pow_check::<BabyBear, Hash>(&challenge, 0, 0));
+    }
+}
@@ -64,10 +64,10 @@ where
     }
 
     /// Perform initial 'execution' setting code + data.
-    /// Additionally, write any 'results' as needed.
+    /// Additionally, write any 'results' as needed. The po2 is written
+    /// afterwards by [Prover::set_po2](crate::prove::Prover::set_po2).
     pub fn execute<R: Rng<F>>(&mut self, iop: &mut WriteIOP<F, R>) {
         iop.write_field_elem_slice(&*self.exec.io.as_slice());
-        iop.write_u32_slice(&[self.exec.po2 as u32]);
     }
 
     fn compute_accum(&mut self) {
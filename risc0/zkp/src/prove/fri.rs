@@ -15,13 +15,15 @@
 use alloc::vec::Vec;
 
 use log::debug;
+use rayon::prelude::*;
 use risc0_core::field::ExtElem;
 
 use crate::{
     core::{hash::HashFn, log2_ceil},
     hal::{Buffer, Hal},
+    params::{pow_challenge, pow_check},
     prove::{merkle::MerkleTreeProver, write_iop::WriteIOP},
-    FRI_FOLD, FRI_MIN_DEGREE, INV_RATE, QUERIES,
+    ProofParams, SealVersion, FRI_FOLD, INV_RATE,
 };
 
 struct ProveRoundInfo<H: Hal> {
@@ -36,7 +38,12 @@ impl<H: Hal> ProveRoundInfo<H> {
     /// produce the evaluations of the polynomial, the merkle tree
     /// committing to the evaluation, and the coefficients of the folded
     /// polynomial.
-    pub fn new(hal: &H, iop: &mut WriteIOP<H::Field, H::Rng>, coeffs: &H::Buffer<H::Elem>) -> Self {
+    pub fn new(
+        hal: &H,
+        iop: &mut WriteIOP<H::Field, H::Rng>,
+        coeffs: &H::Buffer<H::Elem>,
+        queries: usize,
+    ) -> Self {
         debug!("Doing FRI folding");
         let ext_size = H::ExtElem::EXT_SIZE;
         // Get the number of coefficients of the polynomial over the extension field.
@@ -57,7 +64,7 @@ impl<H: Hal> ProveRoundInfo<H> {
             &evaluated,
             domain / FRI_FOLD,
             FRI_FOLD * ext_size,
-            QUERIES,
+            queries,
         );
         // Send the merkle tree (as a commitment) to the virtual IOP verifier
         merkle.commit(iop);
@@ -74,13 +81,13 @@ impl<H: Hal> ProveRoundInfo<H> {
         }
     }
 
-    pub fn prove_query(&mut self, iop: &mut WriteIOP<H::Field, H::Rng>, pos: &mut usize) {
-        // Compute which group we are in
-        let group = *pos % (self.domain / FRI_FOLD);
+    pub fn prove_queries(&mut self, iop: &mut WriteIOP<H::Field, H::Rng>, pos: &mut [usize]) {
+        // Compute which group each query is in
+        for pos in pos.iter_mut() {
+            *pos %= self.domain / FRI_FOLD;
+        }
         // Generate the proof
-        self.merkle.prove(iop, group);
-        // Update pos
-        *pos = group;
+        self.merkle.prove_batch(iop, pos);
     }
 }
 
@@ -89,16 +96,18 @@ pub fn fri_prove<H: Hal, F>(
     hal: &H,
     iop: &mut WriteIOP<H::Field, H::Rng>,
     coeffs: &H::Buffer<H::Elem>,
+    version: SealVersion,
+    params: &ProofParams,
     mut f: F,
 ) where
-    F: FnMut(&mut WriteIOP<H::Field, H::Rng>, usize),
+    F: FnMut(&mut WriteIOP<H::Field, H::Rng>, &[usize]),
 {
     let ext_size = H::ExtElem::EXT_SIZE;
     let orig_domain = coeffs.size() / ext_size * INV_RATE;
     let mut rounds = Vec::new();
     let mut coeffs = coeffs.clone();
-    while coeffs.size() / ext_size > FRI_MIN_DEGREE {
-        let round = ProveRoundInfo::new(hal, iop, &coeffs);
+    while coeffs.size() / ext_size > params.fri_min_degree() {
+        let round = ProveRoundInfo::new(hal, iop, &coeffs, params.queries());
         coeffs = round.coeffs.clone();
         rounds.push(round);
     }
@@ -112,16 +121,44 @@ pub fn fri_prove<H: Hal, F>(
         let digest = H::HashFn::hash_elem_slice(view);
         iop.commit(&digest);
     });
+    // Find the proof of work, if any, which depends on everything committed so
+    // far and must be found before the queries are drawn.
+    if params.pow_bits() > 0 {
+        debug!("Finding a {}-bit proof of work", params.pow_bits());
+        let challenge = pow_challenge(|bits| iop.random_bits(bits));
+        let nonce = (0..=u32::MAX)
+            .into_par_iter()
+            .find_first(|nonce| {
+                pow_check::<H::Field, H::HashFn>(&challenge, *nonce, params.pow_bits())
+            })
+            .expect("No nonce solves the proof of work");
+        iop.write_u32_slice(&[nonce]);
+    }
     // Do queries
     debug!("Doing Queries");
-    for _ in 0..QUERIES {
-        // Get a 'random' index.
-        let mut pos = iop.random_bits(log2_ceil(orig_domain)) as usize;
-        // Do the 'inner' proof for this index
+    let mut prove_queries = |iop: &mut WriteIOP<H::Field, H::Rng>, pos: &mut [usize]| {
+        // Do the 'inner' proof for these indexes
         f(iop, pos);
         // Write the per-round proofs
         for round in rounds.iter_mut() {
-            round.prove_query(iop, &mut pos);
+            round.prove_queries(iop, pos);
+        }
+    };
+    match version {
+        SealVersion::Paths => {
+            for _ in 0..params.queries() {
+                // Get a 'random' index.
+                let mut pos = [iop.random_bits(log2_ceil(orig_domain)) as usize];
+                prove_queries(iop, &mut pos);
+            }
+        }
+        SealVersion::Multiproof => {
+            // No commitments are made while answering queries, so all of the
+            // 'random' indexes can be drawn up front.
+            let mut pos: Vec<usize> = (0..params.queries())
+                .map(|_| iop.random_bits(log2_ceil(orig_domain)) as usize)
+                .collect();
+            prove_queries(iop, &mut pos);
         }
     }
 }
@@ -0,0 +1,118 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prediction of the memory used by the [Prover](super::Prover).
+
+use core::mem::size_of;
+
+use risc0_core::field::ExtElem;
+
+use crate::{core::digest::Digest, hal::Hal, taps::TapSet, ProofParams, FRI_FOLD, INV_RATE};
+
+/// Mirrors the `MemoryTracker` used by the HALs, so that a sequence of
+/// allocations can be replayed without actually allocating anything.
+#[derive(Default)]
+struct MemorySim {
+    total: usize,
+    peak: usize,
+}
+
+impl MemorySim {
+    fn alloc(&mut self, size: usize) {
+        self.total += size;
+        self.peak = self.peak.max(self.total);
+    }
+
+    fn free(&mut self, size: usize) {
+        self.total = self.total.saturating_sub(size);
+    }
+
+    /// Account for the allocations made by a `MerkleTreeProver` over a
+    /// matrix with the given number of rows. The node buffer only lives for
+    /// the duration of tree construction.
+    fn merkle(&mut self, rows: usize) {
+        let nodes = rows * 2 * size_of::<Digest>();
+        self.alloc(nodes);
+        self.free(nodes);
+    }
+}
+
+/// Estimate the peak number of bytes of HAL buffers that are live while
+/// proving a circuit with the given [TapSet] over `2^po2` cycles using the
+/// given [ProofParams].
+///
+/// `globals` holds the size (in elements) of each globals buffer passed to
+/// [Prover::finalize](super::Prover::finalize). The estimate replays the
+/// allocations made for the execution trace and by the
+/// [Prover](super::Prover) for the register groups, the check polynomial, the
+/// DEEP-ALI combos and each FRI round, and so approximates what
+/// [Hal::get_memory_usage] reports for the CPU HAL. Small per-query buffers
+/// are not included.
+pub fn estimate_peak_memory<H: Hal>(
+    taps: &TapSet,
+    globals: &[usize],
+    params: &ProofParams,
+    po2: usize,
+) -> usize {
+    let elem_size = size_of::<H::Elem>();
+    let ext_elem_size = size_of::<H::ExtElem>();
+    let ext_size = H::ExtElem::EXT_SIZE;
+    let cycles = 1 << po2;
+    let domain = cycles * INV_RATE;
+    let mut sim = MemorySim::default();
+
+    // The execution trace and globals are held in `CpuBuffer`s by the
+    // `Executor` and `ProveAdapter` for the duration of the proof.
+    for group in 0..taps.num_groups() {
+        sim.alloc(taps.group_size(group) * cycles * elem_size);
+    }
+    for size in globals {
+        sim.alloc(size * elem_size);
+    }
+
+    // Each register group is copied into the HAL, interpolated in place, and
+    // then evaluated over the expanded domain. Both buffers are retained.
+    for group in 0..taps.num_groups() {
+        let group_size = taps.group_size(group);
+        sim.alloc(group_size * cycles * elem_size);
+        sim.alloc(group_size * domain * elem_size);
+        sim.merkle(domain);
+    }
+    for size in globals {
+        sim.alloc(size * elem_size);
+    }
+
+    // The check polynomial and its evaluation.
+    sim.alloc(ext_size * domain * elem_size);
+    sim.alloc(H::CHECK_SIZE * domain * elem_size);
+    sim.merkle(domain);
+
+    // The DEEP-ALI combos and the final polynomial handed to FRI.
+    sim.alloc(cycles * (taps.combos_size() + 1) * ext_elem_size);
+    sim.alloc(cycles * ext_size * elem_size);
+
+    // Each FRI round evaluates the current polynomial over an expanded domain
+    // and keeps the folded coefficients.
+    let mut size = cycles;
+    while size > params.fri_min_degree() {
+        let round_domain = size * INV_RATE;
+        sim.alloc(round_domain * ext_size * elem_size);
+        sim.merkle(round_domain / FRI_FOLD);
+        size /= FRI_FOLD;
+        sim.alloc(size * ext_size * elem_size);
+    }
+    sim.alloc(size * ext_size * elem_size);
+
+    sim.peak
+}
@@ -105,22 +105,68 @@ impl<H: Hal> MerkleTreeProver<H> {
     /// parts of the protocol, and verification will of course fail if the
     /// wrong row is specified.
     pub fn prove(&self, iop: &mut WriteIOP<H::Field, H::Rng>, idx: usize) -> Vec<H::Elem> {
-        assert!(idx < self.params.row_size);
-        let mut out = Vec::with_capacity(self.params.col_size);
+        self.prove_batch(iop, &[idx]).pop().unwrap()
+    }
+
+    /// Generate a single proof for a batch of indexes, and return the values
+    /// at each requested column.
+    ///
+    /// The format of the proof is:
+    /// 1) The columns of each distinct index, in ascending order.
+    /// 2) For each layer from the leaves up to the top, the 'other' digests
+    ///    that cannot be computed from lower layers, in ascending order.
+    ///
+    /// A batch of one index produces exactly the same proof as [Self::prove].
+    pub fn prove_batch(
+        &self,
+        iop: &mut WriteIOP<H::Field, H::Rng>,
+        idxs: &[usize],
+    ) -> Vec<Vec<H::Elem>> {
+        for &idx in idxs {
+            assert!(idx < self.params.row_size);
+        }
+        let mut unique = idxs.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let mut cols = Vec::with_capacity(unique.len());
         self.matrix.view(|view| {
-            for i in 0..self.params.col_size {
-                out.push(view[idx + i * self.params.row_size]);
+            for idx in unique.iter() {
+                let col: Vec<H::Elem> = (0..self.params.col_size)
+                    .map(|i| view[idx + i * self.params.row_size])
+                    .collect();
+                iop.write_field_elem_slice::<H::Elem>(col.as_slice());
+                cols.push(col);
             }
         });
-        iop.write_field_elem_slice::<H::Elem>(out.as_slice());
-        let mut idx = idx + self.params.row_size;
-        while idx >= 2 * self.params.top_size {
-            let low_bit = idx % 2;
-            idx /= 2;
-            let other_idx = 2 * idx + (1 - low_bit);
-            iop.write_pod_slice(&[self.nodes[other_idx]]);
+
+        let mut layer: Vec<usize> = unique
+            .iter()
+            .map(|idx| idx + self.params.row_size)
+            .collect();
+        while layer
+            .first()
+            .map_or(false, |&idx| idx >= 2 * self.params.top_size)
+        {
+            let mut next = Vec::with_capacity(layer.len());
+            let mut i = 0;
+            while i < layer.len() {
+                let idx = layer[i];
+                if idx % 2 == 0 && layer.get(i + 1) == Some(&(idx + 1)) {
+                    // Both children are known, so the sibling can be omitted.
+                    i += 2;
+                } else {
+                    iop.write_pod_slice(&[self.nodes[idx ^ 1]]);
+                    i += 1;
+                }
+                next.push(idx / 2);
+            }
+            layer = next;
         }
-        out
+
+        idxs.iter()
+            .map(|idx| cols[unique.binary_search(idx).unwrap()].clone())
+            .collect()
     }
 }
 
@@ -270,6 +316,37 @@ mod tests {
         possibly_bad_verify::<PoseidonSuite>(rows, cols, queries, bad_query, manipulate_proof);
     }
 
+    fn batch_verify<HS: HashSuite<BabyBear>>(rows: usize, cols: usize, queries: usize) {
+        let hal = CpuHal::<BabyBear, HS>::new();
+        let prover = init_prover(&hal, rows, cols, queries);
+
+        let mut iop = WriteIOP::<BabyBear, HS::Rng>::new();
+        prover.commit(&mut iop);
+        let idxs: Vec<usize> = (0..queries)
+            .map(|_| iop.rng.random_bits(log2_ceil(rows)) as usize)
+            .collect();
+        let prover_cols = prover.prove_batch(&mut iop, &idxs);
+
+        let mut r_iop = ReadIOP::<BabyBear, HS::Rng>::new(&iop.proof);
+        let verifier = MerkleTreeVerifier::<VerifierHal<HS>>::new(&mut r_iop, rows, cols, queries);
+        let r_idxs: Vec<usize> = (0..queries)
+            .map(|_| r_iop.random_bits(log2_ceil(rows)) as usize)
+            .collect();
+        assert_eq!(idxs, r_idxs);
+        let verifier_cols = verifier.verify_batch(&mut r_iop, &r_idxs).unwrap();
+        r_iop.verify_complete();
+
+        for (query, r_idx) in idxs.iter().enumerate() {
+            assert_eq!(prover_cols[query].as_slice(), verifier_cols[query]);
+            for c_idx in 0..cols {
+                assert_eq!(
+                    verifier_cols[query][c_idx],
+                    BabyBearElem::from((u32::MAX / 2) - ((r_idx + c_idx * rows) as u32))
+                );
+            }
+        }
+    }
+
     fn randomize_sizes() -> (usize, usize, usize) {
         // Chooses random values of `rows`, `cols`, and `queries` such that:
         // `rows` is a power of 2
@@ -324,6 +401,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn merkle_cpu_randomized_batch_verify() {
+        for _rep in 0..100 {
+            let (rows, cols, queries) = randomize_sizes();
+            batch_verify::<ShaSuite>(rows, cols, queries);
+            batch_verify::<PoseidonSuite>(rows, cols, queries);
+        }
+    }
+
     #[test]
     fn merkle_cpu_2_1_1_bad_query() {
         // n.b. since we test bad queries by incrementing the row, we can't test for a
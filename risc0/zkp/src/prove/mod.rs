@@ -23,9 +23,11 @@ mod accum;
 pub mod adapter;
 pub mod executor;
 mod fri;
+mod memory;
 mod merkle;
 pub mod poly_group;
 pub mod prover;
 pub mod write_iop;
 
+pub use memory::estimate_peak_memory;
 pub use prover::Prover;
@@ -16,7 +16,7 @@ use crate::{
     core::log2_ceil,
     hal::{Buffer, Hal},
     prove::merkle::MerkleTreeProver,
-    INV_RATE, QUERIES,
+    INV_RATE,
 };
 
 /// A PolyGroup represents a group of polynomials, all of the same maximum
@@ -63,6 +63,7 @@ impl<'a, H: Hal> PolyGroup<H> {
         coeffs: H::Buffer<H::Elem>,
         count: usize,
         size: usize,
+        queries: usize,
         _name: &'static str,
     ) -> Self {
         assert_eq!(coeffs.size(), count * size);
@@ -71,7 +72,7 @@ impl<'a, H: Hal> PolyGroup<H> {
         hal.batch_expand(&evaluated, &coeffs, count);
         hal.batch_evaluate_ntt(&evaluated, count, log2_ceil(INV_RATE));
         hal.batch_bit_reverse(&coeffs, count);
-        let merkle = MerkleTreeProver::new(hal, &evaluated, domain, count, QUERIES);
+        let merkle = MerkleTreeProver::new(hal, &evaluated, domain, count, queries);
         PolyGroup {
             coeffs,
             count,
@@ -21,9 +21,10 @@ use crate::{
         poly::{poly_divide, poly_interpolate},
     },
     hal::{Buffer, EvalCheck, Hal},
+    params::encode_seal_header,
     prove::{fri::fri_prove, poly_group::PolyGroup, write_iop::WriteIOP},
     taps::TapSet,
-    INV_RATE,
+    ProofParams, SealVersion, INV_RATE,
 };
 
 /// Object to generate a zero-knowledge proof of the execution of some circuit.
@@ -34,6 +35,8 @@ pub struct Prover<'a, H: Hal> {
     groups: Vec<Option<PolyGroup<H>>>,
     cycles: usize,
     po2: usize,
+    seal_version: SealVersion,
+    params: ProofParams,
 }
 
 fn make_coeffs<H: Hal>(hal: &H, buf: H::Buffer<H::Elem>, count: usize) -> H::Buffer<H::Elem> {
@@ -57,21 +60,44 @@ impl<'a, H: Hal> Prover<'a, H> {
                 .collect(),
             cycles: 0,
             po2: usize::MAX,
+            seal_version: SealVersion::default(),
+            params: ProofParams::default(),
         }
     }
 
+    /// Selects the encoding of the seal. This must be called before
+    /// set_po2().
+    pub fn set_seal_version(&mut self, version: SealVersion) {
+        assert_eq!(self.po2, usize::MAX);
+        self.seal_version = version;
+    }
+
+    /// Selects the [ProofParams] used to generate the seal. This must be
+    /// called before set_po2().
+    pub fn set_proof_params(&mut self, params: ProofParams) {
+        assert_eq!(self.po2, usize::MAX);
+        self.params = params;
+    }
+
     /// Accesses the prover's IOP to commit or read random data.
     pub fn iop(&mut self) -> &mut WriteIOP<H::Field, H::Rng> {
         &mut self.iop
     }
 
-    /// Sets the number of cycles to to 2^po2.  This must be called
-    /// once after new() before any commit_group() calls.
+    /// Sets the number of cycles to to 2^po2, and writes it to the IOP along
+    /// with the seal version and proof parameters.  This must be called once
+    /// after new() and after the circuit outputs have been written, before
+    /// any commit_group() calls.
     pub fn set_po2(&mut self, po2: usize) {
         assert_eq!(self.po2, usize::MAX);
         assert_eq!(self.cycles, 0);
         self.po2 = po2;
         self.cycles = 1 << po2;
+        self.iop.write_u32_slice(&[encode_seal_header(
+            self.seal_version,
+            &self.params,
+            po2 as u32,
+        )]);
     }
 
     /// Commits a given buffer to the IOP; the values must not subsequently
@@ -93,6 +119,7 @@ impl<'a, H: Hal> Prover<'a, H> {
             coeffs,
             group_size,
             self.cycles,
+            self.params.queries(),
             "data",
         ));
 
@@ -167,7 +194,14 @@ impl<'a, H: Hal> Prover<'a, H> {
         // invRate*size to 16 polys of size, without actually doing anything.
 
         // Make the PolyGroup + add it to the IOP;
-        let check_group = PolyGroup::new(self.hal, check_poly, H::CHECK_SIZE, self.cycles, "check");
+        let check_group = PolyGroup::new(
+            self.hal,
+            check_poly,
+            H::CHECK_SIZE,
+            self.cycles,
+            self.params.queries(),
+            "check",
+        );
         check_group.merkle.commit(&mut self.iop);
         debug!("checkGroup: {}", check_group.merkle.root());
 
@@ -343,14 +377,21 @@ impl<'a, H: Hal> Prover<'a, H> {
             final_poly_coeffs.size() / H::ExtElem::EXT_SIZE
         );
 
-        fri_prove(self.hal, &mut self.iop, &final_poly_coeffs, |iop, idx| {
-            for pg in self.groups.iter() {
-                let pg = pg.as_ref().unwrap();
-
-                pg.merkle.prove(iop, idx);
-            }
-            check_group.merkle.prove(iop, idx);
-        });
+        fri_prove(
+            self.hal,
+            &mut self.iop,
+            &final_poly_coeffs,
+            self.seal_version,
+            &self.params,
+            |iop, idxs| {
+                for pg in self.groups.iter() {
+                    let pg = pg.as_ref().unwrap();
+
+                    pg.merkle.prove_batch(iop, idxs);
+                }
+                check_group.merkle.prove_batch(iop, idxs);
+            },
+        );
 
         // Return final proof
         let proof = self.iop.proof;
@@ -18,13 +18,17 @@ use risc0_core::field::Field;
 
 use crate::{
     adapter::{CircuitInfo, TapsProvider},
+    params::decode_seal_header,
     taps::TapSet,
-    verify::{read_iop::ReadIOP, Rng},
+    verify::{read_iop::ReadIOP, Rng, VerificationError},
+    ProofParams, SealVersion,
 };
 
 pub struct VerifyAdapter<'a, F: Field, C: CircuitInfo + TapsProvider> {
     circuit: &'a C,
     po2: u32,
+    seal_version: SealVersion,
+    params: ProofParams,
     steps: usize,
     pub out: Option<&'a [F::Elem]>,
     pub mix: Vec<F::Elem>,
@@ -35,6 +39,8 @@ impl<'a, F: Field, C: CircuitInfo + TapsProvider> VerifyAdapter<'a, F, C> {
         VerifyAdapter {
             circuit,
             po2: 0,
+            seal_version: SealVersion::default(),
+            params: ProofParams::default(),
             steps: 0,
             out: None,
             mix: Vec::new(),
@@ -45,14 +51,18 @@ impl<'a, F: Field, C: CircuitInfo + TapsProvider> VerifyAdapter<'a, F, C> {
         self.circuit.get_taps()
     }
 
-    pub fn execute<R: Rng<F>>(&mut self, iop: &mut ReadIOP<'a, F, R>) {
+    pub fn execute<R: Rng<F>>(
+        &mut self,
+        iop: &mut ReadIOP<'a, F, R>,
+    ) -> Result<(), VerificationError> {
         // Read the outputs + size
         self.out = Some(iop.read_field_elem_slice(C::OUTPUT_SIZE));
-        self.po2 = match iop.read_u32s(1) {
-            &[po2] => po2,
+        (self.seal_version, self.params, self.po2) = match iop.read_u32s(1) {
+            &[word] => decode_seal_header(word)?,
             _ => unreachable!(),
         };
         self.steps = 1 << self.po2;
+        Ok(())
     }
 
     pub fn accumulate<R: Rng<F>>(&mut self, iop: &mut ReadIOP<'a, F, R>) {
@@ -63,4 +73,12 @@ impl<'a, F: Field, C: CircuitInfo + TapsProvider> VerifyAdapter<'a, F, C> {
     pub fn po2(&self) -> u32 {
         self.po2
     }
+
+    pub fn seal_version(&self) -> SealVersion {
+        self.seal_version
+    }
+
+    pub fn proof_params(&self) -> &ProofParams {
+        &self.params
+    }
 }
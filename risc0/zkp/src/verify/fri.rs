@@ -19,8 +19,9 @@ use risc0_core::field::{Elem, ExtElem, RootsOfUnity};
 use super::VerifyHal;
 use crate::{
     core::{hash::HashFn, log2_ceil},
+    params::{pow_challenge, pow_check},
     verify::{merkle::MerkleTreeVerifier, read_iop::ReadIOP, VerificationError},
-    FRI_FOLD, FRI_FOLD_PO2, FRI_MIN_DEGREE, INV_RATE, QUERIES,
+    ProofParams, SealVersion, FRI_FOLD, FRI_FOLD_PO2, INV_RATE,
 };
 
 /// VerifyRoundInfo contains the data against which the queries for a particular
@@ -33,44 +34,47 @@ struct VerifyRoundInfo<'a, H: VerifyHal> {
 }
 
 impl<'a, H: VerifyHal> VerifyRoundInfo<'a, H> {
-    pub fn new(iop: &mut ReadIOP<'a, H::Field, H::Rng>, in_domain: usize) -> Self {
+    pub fn new(iop: &mut ReadIOP<'a, H::Field, H::Rng>, in_domain: usize, queries: usize) -> Self {
         let domain = in_domain / FRI_FOLD;
         VerifyRoundInfo {
             domain,
-            merkle: MerkleTreeVerifier::new(iop, domain, FRI_FOLD * H::ExtElem::EXT_SIZE, QUERIES),
+            merkle: MerkleTreeVerifier::new(iop, domain, FRI_FOLD * H::ExtElem::EXT_SIZE, queries),
             mix: iop.random_ext_elem(),
         }
     }
 
-    pub fn verify_query(
+    pub fn verify_queries(
         &mut self,
         hal: &H,
         iop: &mut ReadIOP<'a, H::Field, H::Rng>,
-        pos: &mut usize,
-        goal: &mut H::ExtElem,
+        pos: &mut [usize],
+        goal: &mut [H::ExtElem],
     ) -> Result<(), VerificationError> {
-        let quot = *pos / self.domain;
-        let group = *pos % self.domain;
-        // Get the column data
-        let data = self.merkle.verify(iop, group)?;
-        let data_ext: Vec<_> = (0..FRI_FOLD)
-            .map(|i| {
-                let mut inps = Vec::with_capacity(H::ExtElem::EXT_SIZE);
-                for j in 0..H::ExtElem::EXT_SIZE {
-                    inps.push(data[j * FRI_FOLD + i]);
-                }
-                H::ExtElem::from_subelems(inps)
-            })
-            .collect();
-        // Check the existing goal
-        if data_ext[quot] != *goal {
-            return Err(VerificationError::InvalidProof);
+        let quots: Vec<usize> = pos.iter().map(|pos| *pos / self.domain).collect();
+        for pos in pos.iter_mut() {
+            *pos %= self.domain;
         }
-        // Compute the new goal + pos
+        // Get the column data
+        let data = self.merkle.verify_batch(iop, pos)?;
         let root_po2 = log2_ceil(FRI_FOLD * self.domain);
-        let inv_wk = H::Elem::ROU_REV[root_po2].pow(group);
-        *goal = hal.fold_eval(&mut data_ext.try_into().unwrap(), self.mix * inv_wk);
-        *pos = group;
+        for (i, group) in pos.iter().enumerate() {
+            let data_ext: Vec<_> = (0..FRI_FOLD)
+                .map(|j| {
+                    let mut inps = Vec::with_capacity(H::ExtElem::EXT_SIZE);
+                    for k in 0..H::ExtElem::EXT_SIZE {
+                        inps.push(data[i][k * FRI_FOLD + j]);
+                    }
+                    H::ExtElem::from_subelems(inps)
+                })
+                .collect();
+            // Check the existing goal
+            if data_ext[quots[i]] != goal[i] {
+                return Err(VerificationError::InvalidProof);
+            }
+            // Compute the new goal
+            let inv_wk = H::Elem::ROU_REV[root_po2].pow(*group);
+            goal[i] = hal.fold_eval(&mut data_ext.try_into().unwrap(), self.mix * inv_wk);
+        }
         Ok(())
     }
 }
@@ -79,10 +83,15 @@ pub fn fri_verify<'a, H: VerifyHal + 'a, F>(
     hal: &'a H,
     iop: &mut ReadIOP<'a, H::Field, H::Rng>,
     mut degree: usize,
+    version: SealVersion,
+    params: &ProofParams,
     mut inner: F,
 ) -> Result<(), VerificationError>
 where
-    F: FnMut(&mut ReadIOP<'a, H::Field, H::Rng>, usize) -> Result<H::ExtElem, VerificationError>,
+    F: FnMut(
+        &mut ReadIOP<'a, H::Field, H::Rng>,
+        &[usize],
+    ) -> Result<Vec<H::ExtElem>, VerificationError>,
 {
     let orig_domain = INV_RATE * degree;
     let mut domain = orig_domain;
@@ -90,8 +99,8 @@ where
     let rounds_capacity =
         (log2_ceil((degree + FRI_FOLD - 1) / FRI_FOLD) + FRI_FOLD_PO2 - 1) / FRI_FOLD_PO2;
     let mut rounds = Vec::with_capacity(rounds_capacity);
-    while degree > FRI_MIN_DEGREE {
-        rounds.push(VerifyRoundInfo::new(iop, domain));
+    while degree > params.fri_min_degree() {
+        rounds.push(VerifyRoundInfo::new(iop, domain, params.queries()));
         domain /= FRI_FOLD;
         degree /= FRI_FOLD;
     }
@@ -108,30 +117,58 @@ where
     let final_coeffs = iop.read_field_elem_slice(H::ExtElem::EXT_SIZE * degree);
     let final_digest = H::HashFn::hash_elem_slice(final_coeffs);
     iop.commit(&final_digest);
+    // Check the proof of work, if any
+    if params.pow_bits() > 0 {
+        let challenge = pow_challenge(|bits| iop.random_bits(bits));
+        let nonce = iop.read_u32s(1)[0];
+        if !pow_check::<H::Field, H::HashFn>(&challenge, nonce, params.pow_bits()) {
+            return Err(VerificationError::InvalidProof);
+        }
+    }
     // Get the generator for the final polynomial evaluations
     let gen = <H::Elem as RootsOfUnity>::ROU_FWD[log2_ceil(domain)];
     // Do queries
     let mut poly_buf: Vec<H::ExtElem> = Vec::with_capacity(degree);
-    for _ in 0..QUERIES {
-        let mut pos = iop.random_bits(log2_ceil(orig_domain)) as usize;
-        // Do the 'inner' verification for this index
+    let mut verify_queries = |iop: &mut ReadIOP<'a, H::Field, H::Rng>,
+                              pos: &mut [usize]|
+     -> Result<(), VerificationError> {
+        // Do the 'inner' verification for these indexes
         let mut goal = inner(iop, pos)?;
         // Verify the per-round proofs
         for round in &mut rounds {
-            round.verify_query(hal, iop, &mut pos, &mut goal)?;
+            round.verify_queries(hal, iop, pos, &mut goal)?;
         }
         // Do final verification
-        let x = gen.pow(pos);
+        for (pos, goal) in pos.iter().zip(goal.iter()) {
+            let x = gen.pow(*pos);
 
-        poly_buf.clear();
-        poly_buf.extend((0..degree).map(|i| {
-            H::ExtElem::from_subelems(
-                (0..H::ExtElem::EXT_SIZE).map(|j| final_coeffs[j * degree + i]),
-            )
-        }));
-        let fx = hal.poly_eval(poly_buf.as_slice(), H::ExtElem::from_subfield(&x));
-        if fx != goal {
-            return Err(VerificationError::InvalidProof);
+            poly_buf.clear();
+            poly_buf.extend((0..degree).map(|i| {
+                H::ExtElem::from_subelems(
+                    (0..H::ExtElem::EXT_SIZE).map(|j| final_coeffs[j * degree + i]),
+                )
+            }));
+            let fx = hal.poly_eval(poly_buf.as_slice(), H::ExtElem::from_subfield(&x));
+            if fx != *goal {
+                return Err(VerificationError::InvalidProof);
+            }
+        }
+        Ok(())
+    };
+    match version {
+        SealVersion::Paths => {
+            for _ in 0..params.queries() {
+                let mut pos = [iop.random_bits(log2_ceil(orig_domain)) as usize];
+                verify_queries(iop, &mut pos)?;
+            }
+        }
+        SealVersion::Multiproof => {
+            // The prover draws every query index before answering any of them,
+            // so we do the same.
+            let mut pos: Vec<usize> = (0..params.queries())
+                .map(|_| iop.random_bits(log2_ceil(orig_domain)) as usize)
+                .collect();
+            verify_queries(iop, &mut pos)?;
         }
     }
     Ok(())
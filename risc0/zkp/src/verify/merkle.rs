@@ -146,48 +146,90 @@ impl<'a, H: VerifyHal> MerkleTreeVerifier<'a, H> {
     pub fn verify(
         &self,
         iop: &mut ReadIOP<'a, H::Field, H::Rng>,
-        mut idx: usize,
+        idx: usize,
     ) -> Result<&'a [H::Elem], VerificationError> {
-        if idx >= self.params.row_size {
-            return Err(VerificationError::MerkleQueryOutOfRange {
-                idx: idx,
-                rows: self.params.row_size,
-            });
+        Ok(self.verify_batch(iop, &[idx])?[0])
+    }
+
+    /// Verifies a batch of branches provided by an IOP as a single multiproof,
+    /// returning the column for each of the requested indexes.
+    pub fn verify_batch(
+        &self,
+        iop: &mut ReadIOP<'a, H::Field, H::Rng>,
+        idxs: &[usize],
+    ) -> Result<Vec<&'a [H::Elem]>, VerificationError> {
+        for &idx in idxs {
+            if idx >= self.params.row_size {
+                return Err(VerificationError::MerkleQueryOutOfRange {
+                    idx: idx,
+                    rows: self.params.row_size,
+                });
+            }
         }
-        // Initialize a vector to hold field elements.
-        let out: &[H::Elem] = iop.read_field_elem_slice(self.params.col_size);
-        // Get the hash at the leaf of the tree by hashing these field elements.
-        let mut cur = H::HashFn::hash_elem_slice(out);
-        // Shift idx to start of the row
-        idx += self.params.row_size;
-        while idx >= 2 * self.params.top_size {
-            // low_bit determines whether hash cur at idx is the left (0) or right (1)
-            // child.
-            let low_bit = idx % 2;
-            // Retrieve the other parent from the IOP.
-            let other: &Digest = match iop.read_pod_slice(1) {
-                [other] => other,
-                _ => unreachable!(),
-            };
-            // Now ascend to the parent index, and compute the hash there.
-            idx /= 2;
-            if low_bit == 1 {
-                cur = H::HashFn::hash_pair(&other, &cur);
-            } else {
-                cur = H::HashFn::hash_pair(&cur, &other);
+        let mut unique = idxs.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        // Read the columns of each distinct index, and hash them to get the
+        // leaves of the tree. Indexes are shifted to the start of the row.
+        let cols: Vec<&'a [H::Elem]> = unique
+            .iter()
+            .map(|_| iop.read_field_elem_slice(self.params.col_size))
+            .collect();
+        let mut layer: Vec<(usize, <H::HashFn as HashFn<H::Field>>::DigestPtr)> = unique
+            .iter()
+            .zip(cols.iter())
+            .map(|(idx, col)| (idx + self.params.row_size, H::HashFn::hash_elem_slice(col)))
+            .collect();
+
+        // Ascend one layer at a time, reading only the siblings that cannot be
+        // computed from the layer below.
+        while layer
+            .first()
+            .map_or(false, |(idx, _)| *idx >= 2 * self.params.top_size)
+        {
+            let mut next = Vec::with_capacity(layer.len());
+            let mut iter = layer.into_iter().peekable();
+            while let Some((idx, cur)) = iter.next() {
+                let parent = if idx % 2 == 0 && iter.peek().map(|(next, _)| *next) == Some(idx + 1)
+                {
+                    let (_, other) = iter.next().unwrap();
+                    H::HashFn::hash_pair(&cur, &other)
+                } else {
+                    // Retrieve the other child from the IOP.
+                    let other: &Digest = match iop.read_pod_slice(1) {
+                        [other] => other,
+                        _ => unreachable!(),
+                    };
+                    // low_bit determines whether cur is the left (0) or right (1)
+                    // child.
+                    if idx % 2 == 1 {
+                        H::HashFn::hash_pair(&other, &cur)
+                    } else {
+                        H::HashFn::hash_pair(&cur, &other)
+                    }
+                };
+                next.push((idx / 2, parent));
             }
+            layer = next;
         }
-        // Once we reduce to an index for which we have the hash, check that it's
-        // correct.
-        let present_hash: &Digest = if idx >= self.params.top_size {
-            &self.top[self.params.idx_to_top(idx)]
-        } else {
-            &self.rest[self.params.idx_to_rest(idx)]
-        };
-        if *present_hash == *cur {
-            Ok(out)
-        } else {
-            Err(VerificationError::InvalidProof)
+
+        // Once we reduce to indexes for which we have the hash, check that
+        // they're correct.
+        for (idx, cur) in layer.iter() {
+            let present_hash: &Digest = if *idx >= self.params.top_size {
+                &self.top[self.params.idx_to_top(*idx)]
+            } else {
+                &self.rest[self.params.idx_to_rest(*idx)]
+            };
+            if *present_hash != **cur {
+                return Err(VerificationError::InvalidProof);
+            }
         }
+
+        Ok(idxs
+            .iter()
+            .map(|idx| cols[unique.binary_search(idx).unwrap()])
+            .collect())
     }
 }
@@ -25,16 +25,15 @@ pub(crate) mod merkle;
 pub mod read_iop;
 
 use alloc::{vec, vec::Vec};
-use core::fmt::{self};
-#[cfg(not(target_os = "zkvm"))]
-use core::marker::PhantomData;
+use core::{
+    fmt::{self},
+    marker::PhantomData,
+};
 
-#[cfg(not(target_os = "zkvm"))]
-pub use host::CpuVerifyHal;
+pub use cpu::CpuVerifyHal;
 use risc0_core::field::{Elem, ExtElem, Field, RootsOfUnity};
 
 use self::adapter::VerifyAdapter;
-#[cfg(not(target_os = "zkvm"))]
 pub use crate::core::hash::HashSuite;
 use crate::{
     adapter::{
@@ -47,7 +46,7 @@ use crate::{
     },
     taps::TapSet,
     verify::{fri::fri_verify, merkle::MerkleTreeVerifier, read_iop::ReadIOP},
-    FRI_FOLD, INV_RATE, MAX_CYCLES_PO2, QUERIES,
+    ProofParams, FRI_FOLD, INV_RATE, MAX_CYCLES_PO2,
 };
 
 #[derive(Debug, PartialEq)]
@@ -58,6 +57,8 @@ pub enum VerificationError {
     MerkleQueryOutOfRange { idx: usize, rows: usize },
     InvalidProof,
     JournalDigestMismatch,
+    InputDigestMismatch,
+    ProofParamsTooWeak,
 }
 
 impl fmt::Display for VerificationError {
@@ -74,6 +75,15 @@ impl fmt::Display for VerificationError {
             VerificationError::JournalDigestMismatch => {
                 write!(f, "Journal digest mismatch detected")
             }
+            VerificationError::InputDigestMismatch => {
+                write!(f, "Public input digest mismatch detected")
+            }
+            VerificationError::ProofParamsTooWeak => {
+                write!(
+                    f,
+                    "Seal was made with weaker proof parameters than required"
+                )
+            }
         }
     }
 }
@@ -120,8 +130,7 @@ pub trait VerifyHal {
     ) -> Self::ExtElem;
 }
 
-#[cfg(not(target_os = "zkvm"))]
-mod host {
+mod cpu {
     use core::{cell::RefCell, iter::zip};
 
     use risc0_core::field::Field;
@@ -270,13 +279,36 @@ mod host {
 }
 
 /// Verify a seal is valid for the given circuit, code, and globals
-#[tracing::instrument(skip_all)]
+///
+/// Seals made with [ProofParams] weaker than [ProofParams::DEFAULT] are
+/// rejected; use [verify_with_params] to choose a different minimum.
 pub fn verify<'a, H, C, CheckCode>(
     hal: &'a H,
     circuit: &C,
     seal: &'a [u32],
     check_code: CheckCode,
 ) -> Result<(), VerificationError>
+where
+    H: VerifyHal,
+    C: CircuitInfo + TapsProvider,
+    CheckCode: Fn(u32, &Digest) -> Result<(), VerificationError>,
+{
+    verify_with_params(hal, circuit, seal, check_code, &ProofParams::DEFAULT)
+}
+
+/// Verify a seal is valid for the given circuit, code, and globals, and was
+/// made with [ProofParams] at least as strong as `min_params`.
+///
+/// The [ProofParams] used by the prover are read from the seal, and seals that
+/// use anything other than one of the presets are rejected.
+#[tracing::instrument(skip_all)]
+pub fn verify_with_params<'a, H, C, CheckCode>(
+    hal: &'a H,
+    circuit: &C,
+    seal: &'a [u32],
+    check_code: CheckCode,
+    min_params: &ProofParams,
+) -> Result<(), VerificationError>
 where
     H: VerifyHal,
     C: CircuitInfo + TapsProvider,
@@ -293,7 +325,7 @@ where
     let mut iop = ReadIOP::<H::Field, H::Rng>::new(seal);
 
     // Read any execution state
-    adapter.execute(&mut iop);
+    adapter.execute(&mut iop)?;
 
     // Get the size
     let po2 = adapter.po2();
@@ -302,6 +334,13 @@ where
     let domain = INV_RATE * size;
     // debug!("size = {size}, po2 = {po2}");
 
+    // Get the parameters chosen by the prover
+    let params = *adapter.proof_params();
+    if params.security_bits() < min_params.security_bits() {
+        return Err(VerificationError::ProofParamsTooWeak);
+    }
+    let queries = params.queries();
+
     // Get taps and compute sizes
     let code_size = taps.group_size(REGISTER_GROUP_CODE);
     let data_size = taps.group_size(REGISTER_GROUP_DATA);
@@ -310,7 +349,7 @@ where
     // Get merkle root for the code merkle tree.
     // The code merkle tree contains the control instructions for the zkVM.
     hal.debug("code_merkle");
-    let code_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, code_size, QUERIES);
+    let code_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, code_size, queries);
     // debug!("codeRoot = {}", code_merkle.root());
     check_code(po2, code_merkle.root())?;
 
@@ -319,7 +358,7 @@ where
     // including memory accesses as well as the permutation of those memory
     // accesses sorted by location used by PLONK.
     hal.debug("data_merkle");
-    let data_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, data_size, QUERIES);
+    let data_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, data_size, queries);
     // debug!("dataRoot = {}", data_merkle.root());
 
     // Prep accumulation
@@ -336,7 +375,7 @@ where
     // values (see PLOOKUP paper for details). This permutation is used to
     // implement a look-up table.
     hal.debug("accum_merkle");
-    let accum_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, accum_size, QUERIES);
+    let accum_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, accum_size, queries);
     // debug!("accumRoot = {}", accum_merkle.root());
 
     // Get a pseudorandom value with which to mix the constraint polynomials.
@@ -344,7 +383,7 @@ where
     let poly_mix = iop.random_ext_elem();
 
     hal.debug("check_merkle");
-    let check_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, H::CHECK_SIZE, QUERIES);
+    let check_merkle = MerkleTreeVerifier::<H>::new(&mut iop, domain, H::CHECK_SIZE, queries);
     // debug!("checkRoot = {}", check_merkle.root());
 
     // Get a pseudorandom DEEP query point
@@ -465,17 +504,25 @@ where
         hal,
         &mut iop,
         size,
-        |iop: &mut ReadIOP<H::Field, _>, idx: usize| -> Result<H::ExtElem, VerificationError> {
+        adapter.seal_version(),
+        &params,
+        |iop: &mut ReadIOP<H::Field, _>,
+         idxs: &[usize]|
+         -> Result<Vec<H::ExtElem>, VerificationError> {
             // hal.debug("fri_verify");
-            let x = gen.pow(idx);
-            let rows = [
-                accum_merkle.verify(iop, idx)?,
-                code_merkle.verify(iop, idx)?,
-                data_merkle.verify(iop, idx)?,
-            ];
-            let check_row = check_merkle.verify(iop, idx)?;
-            let ret = hal.fri_eval_taps(taps, mix, &combo_u, check_row, back_one, x, z, rows);
-            Ok(ret)
+            let accum_rows = accum_merkle.verify_batch(iop, idxs)?;
+            let code_rows = code_merkle.verify_batch(iop, idxs)?;
+            let data_rows = data_merkle.verify_batch(iop, idxs)?;
+            let check_rows = check_merkle.verify_batch(iop, idxs)?;
+            Ok(idxs
+                .iter()
+                .enumerate()
+                .map(|(i, idx)| {
+                    let x = gen.pow(*idx);
+                    let rows = [accum_rows[i], code_rows[i], data_rows[i]];
+                    hal.fri_eval_taps(taps, mix, &combo_u, check_rows[i], back_one, x, z, rows)
+                })
+                .collect())
         },
     )?;
     iop.verify_complete();
@@ -25,6 +25,7 @@ use criterion::{
     black_box, criterion_group, criterion_main, Bencher, BenchmarkId, Criterion, SamplingMode,
     Throughput,
 };
+use k256::ecdsa::signature::Signer;
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
@@ -32,22 +33,75 @@ use rand::{
 use risc0_zkvm::{serde::to_vec, Executor, ExecutorEnv};
 use risc0_zkvm_methods::{
     bench::{BenchmarkSpec, SpecWithIters},
-    BENCH_ELF,
+    multi_test::{EcdsaCurve, MultiTestSpec},
+    BENCH_ELF, BENCH_RECLAIM_ELF, MULTI_TEST_ELF, MULTI_TEST_ID,
 };
 
-fn run_guest(spec: SpecWithIters) -> Duration {
+fn executor(elf: &[u8], spec: &SpecWithIters) -> Executor<'static> {
     let env = ExecutorEnv::builder()
-        .add_input(&to_vec(&spec).unwrap())
+        .add_input(&to_vec(spec).unwrap())
         .build();
-    let mut exec = Executor::from_elf(env, BENCH_ELF).unwrap();
+    Executor::from_elf(env, elf).unwrap()
+}
+
+fn run_guest(elf: &[u8], spec: SpecWithIters) -> Duration {
+    let mut exec = executor(elf, &spec);
 
     let start = Instant::now();
     black_box(exec.run().unwrap());
     start.elapsed()
 }
 
+/// Returns the number of instruction cycles the guest takes to run `spec`.
+fn guest_cycles(elf: &[u8], spec: SpecWithIters) -> usize {
+    let session = executor(elf, &spec).run().unwrap();
+    session
+        .resolve()
+        .unwrap()
+        .iter()
+        .map(|segment| segment.insn_cycles)
+        .sum()
+}
+
 fn guest_iter(b: &mut Bencher, spec: BenchmarkSpec) {
-    b.iter_custom(|iters| run_guest(SpecWithIters(spec.clone(), iters)))
+    guest_iter_elf(b, BENCH_ELF, spec)
+}
+
+fn guest_iter_elf(b: &mut Bencher, elf: &[u8], spec: BenchmarkSpec) {
+    b.iter_custom(|iters| run_guest(elf, SpecWithIters(spec.clone(), iters)))
+}
+
+/// Benchmarks running `MULTI_TEST_ELF` on `inputs`, which are not iterated
+/// over, and prints the number of instruction cycles it takes.
+fn multi_test_iter(c: &mut Criterion, name: &str, inputs: &[Vec<u32>]) {
+    let executor = || {
+        let mut env = ExecutorEnv::builder();
+        for input in inputs {
+            env.add_input(input);
+        }
+        Executor::from_elf(env.build(), MULTI_TEST_ELF).unwrap()
+    };
+    let cycles: usize = executor()
+        .run()
+        .unwrap()
+        .resolve()
+        .unwrap()
+        .iter()
+        .map(|segment| segment.insn_cycles)
+        .sum();
+    println!("{name}: {cycles} cycles");
+    c.bench_function(name, |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                let mut exec = executor();
+                let start = Instant::now();
+                black_box(exec.run().unwrap());
+                total += start.elapsed();
+            }
+            total
+        })
+    });
 }
 
 fn rand_buffer<T>(len: usize) -> Vec<T>
@@ -171,6 +225,69 @@ pub fn bench(c: &mut Criterion) {
         );
     }
     memcpy_group.finish();
+
+    // Compares the default bump allocator with the `heap-reclaim` allocator
+    // on a guest that keeps a few buffers alive while allocating many more.
+    // The cycle counts are printed as well, since they are what determines
+    // the cost of proving.
+    let mut alloc_group = c.benchmark_group("alloc_churn");
+    alloc_group.sampling_mode(SamplingMode::Flat);
+    for buf_bytes in [16usize, 256, 1024] {
+        let spec = BenchmarkSpec::AllocChurn {
+            len: buf_bytes,
+            live: 8,
+        };
+        for (name, elf) in [("bump", BENCH_ELF), ("reclaim", BENCH_RECLAIM_ELF)] {
+            let cycles = guest_cycles(elf, SpecWithIters(spec.clone(), 1000));
+            println!("alloc_churn/{name}/{buf_bytes}: {cycles} cycles per 1000 iterations");
+            alloc_group.bench_with_input(BenchmarkId::new(name, buf_bytes), &spec, |b, spec| {
+                guest_iter_elf(b, elf, spec.clone())
+            });
+        }
+    }
+    alloc_group.finish();
+
+    // Verifying a receipt inside the guest, which is what composing proofs
+    // costs. The inner receipt is proven once up front.
+    let env = ExecutorEnv::builder()
+        .add_input(&to_vec(&MultiTestSpec::DoNothing).unwrap())
+        .build();
+    let inner = Executor::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap()
+        .prove()
+        .unwrap();
+    multi_test_iter(
+        c,
+        "verify_receipt",
+        &[
+            to_vec(&MultiTestSpec::VerifyReceipt {
+                image_id: MULTI_TEST_ID,
+            })
+            .unwrap(),
+            to_vec(&inner).unwrap(),
+        ],
+    );
+
+    let key = k256::ecdsa::SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+    let message = b"message to sign";
+    let signature: k256::ecdsa::Signature = key.sign(message);
+    multi_test_iter(
+        c,
+        "ecdsa_verify",
+        &[to_vec(&MultiTestSpec::EcdsaVerify {
+            curve: EcdsaCurve::Secp256k1,
+            public_key: key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes()
+                .to_vec(),
+            message: message.to_vec(),
+            signature: signature.to_bytes().to_vec(),
+        })
+        .unwrap()],
+    );
 }
 
 criterion_group!(name = benches;
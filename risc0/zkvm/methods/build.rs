@@ -30,6 +30,15 @@ fn main() {
             GuestOptions {
                 features: Vec::new(),
                 std: false,
+                ..Default::default()
+            },
+        ),
+        (
+            "risc0-zkvm-methods-reclaim",
+            GuestOptions {
+                features: Vec::new(),
+                std: true,
+                ..Default::default()
             },
         ),
         (
@@ -37,6 +46,7 @@ fn main() {
             GuestOptions {
                 features: vec!["test_feature1".to_string(), "test_feature2".to_string()],
                 std: true,
+                ..Default::default()
             },
         ),
     ]);
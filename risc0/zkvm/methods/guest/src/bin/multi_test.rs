@@ -18,16 +18,29 @@
 #![no_std]
 
 extern crate alloc;
-use alloc::vec;
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use core::arch::asm;
 
 use getrandom::getrandom;
 use risc0_zkp::core::hash::sha::testutil::test_sha_impl;
 use risc0_zkvm::{
-    guest::{env, memory_barrier, sha},
+    guest::{
+        bigint::{
+            ec::{verify_ecdsa, AffinePoint, Curve, Secp256k1, Secp256k1Base, Signature, P256},
+            FieldElement, U256,
+        },
+        env, fs, logger, memory_barrier, sha,
+    },
+    serde::to_vec_tagged,
     sha::{Digest, Sha256},
 };
-use risc0_zkvm_methods::multi_test::{MultiTestSpec, SYS_MULTI_TEST};
+use risc0_zkvm_methods::multi_test::{
+    AddOne, EcdsaCurve, MultiTestSpec, TaggedInput, SYS_MULTI_TEST,
+};
 use risc0_zkvm_platform::{
     fileno,
     syscall::{bigint, sys_bigint, sys_read, sys_write},
@@ -47,7 +60,7 @@ fn profile_test_func2() {
     unsafe { asm!("nop") }
 }
 
-pub fn main() {
+pub fn main() -> Result<(), String> {
     let impl_select: MultiTestSpec = env::read();
     match impl_select {
         MultiTestSpec::DoNothing => {}
@@ -121,6 +134,16 @@ pub fn main() {
                 input_len = input.len();
             }
         }
+        MultiTestSpec::Rpc { values } => {
+            let reply = AddOne::call(&values).unwrap();
+            env::commit(&reply);
+        }
+        MultiTestSpec::InputRegion { start, end } => {
+            // Copying in the whole blob must leave the earlier slice intact.
+            let part = env::input_region_range(start as usize..end as usize);
+            let all = env::input_region();
+            env::commit(&(all, part));
+        }
         MultiTestSpec::DoRandom => {
             // Test random number generation in the zkvm
             let mut rand_buf = [0u8; 7];
@@ -191,5 +214,76 @@ pub fn main() {
             let f = black_box(1.0_f32);
             black_box(f.min(1.0));
         }
+        MultiTestSpec::Exit { code } => {
+            env::commit(&code);
+            env::exit(code);
+        }
+        MultiTestSpec::Reject { reason } => return Err(reason),
+        MultiTestSpec::PublicInput => env::commit(&(env::public_input().len() as u32)),
+        MultiTestSpec::VerifyReceipt { image_id } => {
+            let (receipt, exit_code) =
+                env::read_verified_receipt(image_id).map_err(|err| err.to_string())?;
+            env::commit(&(receipt.journal, exit_code));
+        }
+        MultiTestSpec::ReadFile { path } => {
+            let len = fs::metadata(&path)
+                .ok()
+                .map(|metadata| metadata.len() as u32);
+            env::commit(&len);
+            env::commit(&fs::read_to_string(&path).ok());
+        }
+        MultiTestSpec::ReadTagged => {
+            let inputs: Vec<TaggedInput> = env::read_tagged();
+            env::commit_slice(&to_vec_tagged(&inputs).unwrap());
+        }
+        MultiTestSpec::TryRead => {
+            let input: (u32, String) = env::try_read().map_err(|err| err.to_string())?;
+            env::commit(&input);
+        }
+        MultiTestSpec::BigIntField { x, y } => {
+            let x = FieldElement::<Secp256k1Base>::reduce(U256(x));
+            let y = FieldElement::<Secp256k1Base>::reduce(U256(y));
+            env::commit(&(
+                (x * y).value().0,
+                x.pow(y.value()).value().0,
+                x.inv().map(|inv| inv.value().0),
+            ));
+        }
+        MultiTestSpec::EcdsaVerify {
+            curve,
+            public_key,
+            message,
+            signature,
+        } => {
+            fn verify<C: Curve>(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+                match (
+                    AffinePoint::<C>::from_sec1(public_key),
+                    Signature::<C>::from_bytes(signature),
+                ) {
+                    (Some(public_key), Some(signature)) => {
+                        verify_ecdsa(&public_key, message, &signature)
+                    }
+                    _ => false,
+                }
+            }
+            let valid = match curve {
+                EcdsaCurve::Secp256k1 => verify::<Secp256k1>(&public_key, &message, &signature),
+                EcdsaCurve::P256 => verify::<P256>(&public_key, &message, &signature),
+            };
+            env::commit(&valid);
+        }
+        MultiTestSpec::Log => {
+            logger::init();
+            log::error!("error");
+            log::warn!("warn");
+            log::info!("info");
+            log::debug!("debug");
+            log::trace!("trace");
+            env::commit(&(log::max_level() as u32));
+        }
+        MultiTestSpec::MerkleRoot { proof, leaves } => {
+            env::commit(&proof.root(&leaves));
+        }
     }
+    Ok(())
 }
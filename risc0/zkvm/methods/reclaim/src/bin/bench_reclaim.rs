@@ -14,22 +14,17 @@
 
 #![no_main]
 
-use risc0_zkvm::{
-    sha::{Digest, DIGEST_WORDS},
-    SessionReceipt,
-};
+use risc0_zkvm::guest::env;
+use risc0_zkvm_methods::bench::{alloc_churn, BenchmarkSpec, SpecWithIters};
 
-// This binary is here as a way to check which deps are included
-// when building for the wasm32-unknown-unknown target.
-// Eventually this program should run a full verify with a real receipt.
+risc0_zkvm::entry!(main);
 
-#[no_mangle]
-fn _start() {
-    // TODO: use a real receipt and image_id
-    let receipt = SessionReceipt {
-        segments: Vec::new(),
-        journal: Vec::new(),
-    };
-    let image_id = Digest::from([0; DIGEST_WORDS]);
-    receipt.verify(image_id).unwrap();
+// The allocator benchmarks from `bench`, built with the `heap-reclaim`
+// allocator so that the two can be compared.
+pub fn main() {
+    let SpecWithIters(spec, iters) = env::read();
+    match spec {
+        BenchmarkSpec::AllocChurn { len, live } => alloc_churn(len, live, iters),
+        spec => panic!("Unsupported benchmark: {spec:?}"),
+    }
 }
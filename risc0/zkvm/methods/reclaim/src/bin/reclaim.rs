@@ -0,0 +1,56 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+use std::{
+    alloc::{alloc, realloc, Layout},
+    collections::VecDeque,
+};
+
+use risc0_zkvm::guest::{env, memory_barrier};
+use risc0_zkvm_platform::memory;
+
+risc0_zkvm::entry!(main);
+
+// Checks the `heap-reclaim` allocator: `iters` buffers of `len` bytes are
+// allocated while only the `live` most recent ones are kept, and sizes that
+// the heap can't hold are refused rather than overflowing.
+pub fn main() {
+    let (len, live, iters): (usize, usize, usize) = env::read();
+
+    // The buffers are never written to, so that the total can exceed the heap
+    // without costing many cycles.
+    let mut bufs = VecDeque::with_capacity(live + 1);
+    for _ in 0..iters {
+        let buf: Vec<u8> = Vec::with_capacity(len);
+        memory_barrier(buf.as_ptr());
+        bufs.push_back(buf);
+        if bufs.len() > live {
+            bufs.pop_front();
+        }
+    }
+
+    for size in [memory::HEAP.len_bytes() + 1, isize::MAX as usize - 3] {
+        let layout = Layout::from_size_align(size, 4).unwrap();
+        assert!(unsafe { alloc(layout) }.is_null(), "{size}");
+    }
+
+    let layout = Layout::from_size_align(16, 4).unwrap();
+    unsafe {
+        let ptr = alloc(layout);
+        assert!(!ptr.is_null());
+        assert!(realloc(ptr, layout, memory::HEAP.len_bytes() + 1).is_null());
+    }
+}
@@ -14,8 +14,9 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{collections::VecDeque, vec::Vec};
 
+use risc0_zkvm::guest::memory_barrier;
 use serde::{Deserialize, Serialize};
 
 // Benchmark support structures for communication between host and guest.
@@ -33,7 +34,26 @@ pub enum BenchmarkSpec {
     Memset {
         len: usize,
     },
+    AllocChurn {
+        len: usize,
+        live: usize,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SpecWithIters(pub BenchmarkSpec, pub u64);
+
+/// Allocate and fill a `len` byte buffer on each of `iters` iterations, keeping
+/// only the `live` most recent buffers alive. Shared by the benchmark guests
+/// so that allocators can be compared on the same workload.
+pub fn alloc_churn(len: usize, live: usize, iters: u64) {
+    let mut bufs = VecDeque::with_capacity(live + 1);
+    for i in 0..iters {
+        let buf = alloc::vec![i as u8; len];
+        memory_barrier(buf.as_ptr());
+        bufs.push_back(buf);
+        if bufs.len() > live {
+            bufs.pop_front();
+        }
+    }
+}
@@ -15,9 +15,9 @@
 // Definitions for test selection codes used by the "multi_test" test.
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
-use risc0_zkvm::declare_syscall;
+use risc0_zkvm::{declare_syscall, merkle::MultiProof, sha::Digest};
 use risc0_zkvm_platform::syscall::bigint;
 use serde::{Deserialize, Serialize};
 
@@ -62,6 +62,82 @@ pub enum MultiTestSpec {
         cycles: u32,
     },
     LibM,
+    Rpc {
+        values: Vec<u32>,
+    },
+    InputRegion {
+        /// Byte range of the input region to copy in first.
+        start: u32,
+        end: u32,
+    },
+    Exit {
+        code: u8,
+    },
+    /// Return the reason as an error from the fallible entrypoint.
+    Reject {
+        reason: String,
+    },
+    /// Commit the length of the public input.
+    PublicInput,
+    /// Read a receipt for the given image ID, and commit its journal and exit
+    /// code.
+    VerifyReceipt {
+        image_id: [u32; 8],
+    },
+    /// Commit the length and contents of a file in the virtual filesystem,
+    /// or `None` if it doesn't exist.
+    ReadFile {
+        path: String,
+    },
+    /// Read a `(u32, String)` from the host with `env::try_read`, and commit
+    /// it, or return the error from the fallible entrypoint.
+    TryRead,
+    /// Commit `x * y`, `x^y` and the inverse of `x` in the secp256k1 base
+    /// field, computed with `risc0_zkvm::guest::bigint`.
+    BigIntField {
+        x: [u32; bigint::WIDTH_WORDS],
+        y: [u32; bigint::WIDTH_WORDS],
+    },
+    /// Verify an ECDSA signature over the SHA-256 of `message` with
+    /// `risc0_zkvm::guest::bigint`, and commit whether it is valid.
+    EcdsaVerify {
+        curve: EcdsaCurve,
+        public_key: Vec<u8>,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+    },
+    /// Log a message at each level through the `log` facade, and commit the
+    /// level filter requested by the host.
+    Log,
+    /// Read a `Vec<TaggedInput>` with `env::read_tagged`, and commit it in the
+    /// self-describing encoding.
+    ReadTagged,
+    /// Commit the root computed from `proof` and `leaves`, or `None` if they
+    /// don't match.
+    MerkleRoot {
+        proof: MultiProof,
+        leaves: Vec<(usize, Digest)>,
+    },
+}
+
+/// An input that can only be read in the self-describing encoding.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum TaggedInput {
+    Number(u32),
+    Name(String),
+    Point { x: i32, y: Option<i32> },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum EcdsaCurve {
+    Secp256k1,
+    P256,
 }
 
 declare_syscall!(pub SYS_MULTI_TEST);
+
+risc0_zkvm::rpc_service! {
+    /// Adds one to each value, for testing typed host calls.
+    pub AddOne(version = 1): Vec<u32> => Vec<u32>
+}
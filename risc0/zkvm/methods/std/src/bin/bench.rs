@@ -18,7 +18,7 @@ use risc0_zkvm::{
     guest::{env, memory_barrier, sha},
     sha::Sha256,
 };
-use risc0_zkvm_methods::bench::{BenchmarkSpec, SpecWithIters};
+use risc0_zkvm_methods::bench::{alloc_churn, BenchmarkSpec, SpecWithIters};
 
 risc0_zkvm::entry!(main);
 
@@ -61,5 +61,6 @@ pub fn main() {
                 memory_barrier(&dst_slice);
             }
         }
+        BenchmarkSpec::AllocChurn { len, live } => alloc_churn(len, live, iters),
     }
 }
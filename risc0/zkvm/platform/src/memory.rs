@@ -58,6 +58,11 @@ pub const STACK: Region = Region::new(0x0000_0400, mb(8) - kb(1));
 pub const DATA: Region = Region::new(0x0008_0000, mb(24));
 pub const HEAP: Region = Region::new(0x0200_0000, mb(80));
 pub const TEXT: Region = Region::new(0x0700_0000, mb(80));
-pub const SYSTEM: Region = Region::new(0x0C00_0000, mb(16));
+/// Only the registers at the start of this region are used.
+pub const SYSTEM: Region = Region::new(0x0C00_0000, mb(1));
+/// Holds the pages of the input blob that the guest has copied in from the
+/// host, so that it does not take up heap memory. This is carved out of the
+/// unused part of [SYSTEM], and nothing is linked here.
+pub const INPUT: Region = Region::new(0x0C10_0000, mb(15));
 pub const PAGE_TABLE: Region = Region::new(0x0D00_0000, mb(16));
 pub const PRE_LOAD: Region = Region::new(0x0D70_0000, mb(9));
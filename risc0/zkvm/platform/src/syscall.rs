@@ -117,13 +117,20 @@ macro_rules! declare_syscall {
 }
 
 pub mod nr {
+    declare_syscall!(pub SYS_CLOSE);
     declare_syscall!(pub SYS_CYCLE_COUNT);
     declare_syscall!(pub SYS_GETENV);
+    declare_syscall!(pub SYS_INPUT);
     declare_syscall!(pub SYS_LOG);
+    declare_syscall!(pub SYS_LOG_LEVEL);
+    declare_syscall!(pub SYS_LOG_RECORD);
+    declare_syscall!(pub SYS_OPEN);
     declare_syscall!(pub SYS_PANIC);
+    declare_syscall!(pub SYS_PUBLIC_INPUT);
     declare_syscall!(pub SYS_RANDOM);
     declare_syscall!(pub SYS_READ_AVAIL);
     declare_syscall!(pub SYS_READ);
+    declare_syscall!(pub SYS_STAT);
     declare_syscall!(pub SYS_WRITE);
 }
 
@@ -227,6 +234,21 @@ pub unsafe extern "C" fn sys_halt(user_exit: u8, out_state: *const [u32; DIGEST_
     unimplemented!()
 }
 
+/// Checks that `digest` matches the digest of the public input, as recorded
+/// in the receipt. Execution fails if it does not.
+#[inline(always)]
+#[no_mangle]
+pub unsafe extern "C" fn sys_input(digest: *const [u32; DIGEST_WORDS]) {
+    #[cfg(target_os = "zkvm")]
+    asm!(
+        "ecall",
+        in("t0") ecall::INPUT,
+        in("a0") digest,
+    );
+    #[cfg(not(target_os = "zkvm"))]
+    unimplemented!()
+}
+
 #[inline(always)]
 #[no_mangle]
 pub unsafe extern "C" fn sys_pause(user_exit: u8, out_state: *const [u32; DIGEST_WORDS]) {
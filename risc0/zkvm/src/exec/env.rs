@@ -16,25 +16,41 @@
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     io::{BufRead, BufReader, Cursor, Read, Write},
+    path::Path,
     rc::Rc,
 };
 
 use anyhow::Result;
 use bytemuck::Pod;
+use log::LevelFilter;
+use risc0_core::field::baby_bear::BabyBear;
+use risc0_zkp::{
+    core::hash::sha::{cpu::Impl as CpuImpl, Sha256HashSuite},
+    MAX_CYCLES_PO2, MIN_CYCLES_PO2,
+};
 use risc0_zkvm_platform::{
     fileno,
+    memory::INPUT,
     syscall::{
-        nr::{SYS_GETENV, SYS_READ, SYS_READ_AVAIL, SYS_WRITE},
+        nr::{
+            SYS_CLOSE, SYS_GETENV, SYS_INPUT, SYS_LOG_LEVEL, SYS_OPEN, SYS_PUBLIC_INPUT, SYS_READ,
+            SYS_READ_AVAIL, SYS_STAT, SYS_WRITE,
+        },
         SyscallName,
     },
 };
 
 use super::{
     io::{slice_io_from_fn, syscalls, PosixIo, SliceIo, Syscall, SyscallTable},
+    vfs::{Vfs, VfsFile},
     TraceEvent,
 };
+use crate::{
+    prove::cpu::estimate_peak_memory,
+    rpc::{handle_call, Service},
+};
 
 /// The default segment limit specified in powers of 2 cycles. Choose this value
 /// to try and fit with 8GB of RAM.
@@ -58,6 +74,10 @@ pub struct ExecutorEnv<'a> {
     syscalls: SyscallTable<'a>,
     pub(crate) io: Rc<RefCell<PosixIo<'a>>>,
     input: Vec<u8>,
+    input_region: &'a [u8],
+    pub(crate) public_input: Vec<u8>,
+    files: BTreeMap<String, VfsFile>,
+    log_level: Option<LevelFilter>,
     pub(crate) trace_callback: Option<Rc<RefCell<dyn FnMut(TraceEvent) -> Result<()> + 'a>>>,
 }
 
@@ -96,6 +116,10 @@ impl<'a> Default for ExecutorEnvBuilder<'a> {
                 syscalls: Default::default(),
                 io: Default::default(),
                 input: Default::default(),
+                input_region: Default::default(),
+                public_input: Default::default(),
+                files: Default::default(),
+                log_level: Default::default(),
                 trace_callback: Default::default(),
             },
         }
@@ -107,6 +131,9 @@ impl<'a> ExecutorEnvBuilder<'a> {
     pub fn build(&mut self) -> ExecutorEnv<'a> {
         let mut result = self.clone();
         let getenv = syscalls::Getenv(self.inner.env_vars.clone());
+        let input_region = syscalls::InputRegion(self.inner.input_region);
+        let public_input = syscalls::PublicInput(self.inner.public_input.clone());
+        let log_level = syscalls::LogLevel(self.inner.log_level.unwrap_or_else(log::max_level));
         if !self.inner.input.is_empty() {
             let reader = Cursor::new(self.inner.input.clone());
             result
@@ -116,8 +143,15 @@ impl<'a> ExecutorEnvBuilder<'a> {
                 .with_read_fd(fileno::STDIN, reader);
         }
         let io = result.inner.io.clone();
+        let vfs = Rc::new(RefCell::new(Vfs::new(self.inner.files.clone(), io.clone())));
         result
+            .syscall(SYS_OPEN, vfs.clone())
+            .syscall(SYS_STAT, vfs.clone())
+            .syscall(SYS_CLOSE, vfs)
             .syscall(SYS_GETENV, getenv)
+            .syscall(SYS_INPUT, input_region)
+            .syscall(SYS_LOG_LEVEL, log_level)
+            .syscall(SYS_PUBLIC_INPUT, public_input)
             .syscall(SYS_READ, io.clone())
             .syscall(SYS_READ_AVAIL, io.clone())
             .syscall(SYS_WRITE, io);
@@ -130,6 +164,19 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set the segment limit to the largest power of 2 cycles whose segments
+    /// are estimated to fit within `bytes` of memory when proven by the CPU
+    /// prover with SHA-256.
+    ///
+    /// If no segment size fits, the smallest supported segment size is used.
+    pub fn segment_memory_budget(&mut self, bytes: usize) -> &mut Self {
+        let po2 = (MIN_CYCLES_PO2..=MAX_CYCLES_PO2)
+            .rev()
+            .find(|&po2| estimate_peak_memory::<Sha256HashSuite<BabyBear, CpuImpl>>(po2) <= bytes)
+            .unwrap_or(MIN_CYCLES_PO2);
+        self.segment_limit_po2(po2)
+    }
+
     /// Set a session limit, specified in number of cycles.
     pub fn session_limit(&mut self, limit: usize) -> &mut Self {
         self.inner.session_limit = limit;
@@ -164,6 +211,64 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Provide a blob which the guest can copy into its `memory::INPUT`
+    /// region with `risc0_zkvm::guest::env::input_region`, and access there
+    /// without allocating heap memory for it.
+    ///
+    /// Panics if the blob does not fit in the region.
+    pub fn input_region(&mut self, blob: &'a [u8]) -> &mut Self {
+        assert!(
+            blob.len() <= INPUT.len_bytes(),
+            "Input region of {} bytes exceeds the {} bytes available",
+            blob.len(),
+            INPUT.len_bytes()
+        );
+        self.inner.input_region = blob;
+        self
+    }
+
+    /// Add public input, which the guest reads with
+    /// `risc0_zkvm::guest::env::public_input`.
+    ///
+    /// Unlike other input, the SHA-256 of the public input is bound into the
+    /// receipt, which carries the public input itself in
+    /// [SessionReceipt::input](crate::SessionReceipt::input) so that a
+    /// verifier can check what the guest was given.
+    pub fn public_input<T: Pod>(&mut self, slice: &[T]) -> &mut Self {
+        self.inner
+            .public_input
+            .extend_from_slice(bytemuck::cast_slice(slice));
+        self
+    }
+
+    /// Add a file at `path` with the given contents to the guest's read-only
+    /// filesystem, which the guest accesses with `risc0_zkvm::guest::fs`.
+    pub fn file(&mut self, path: &str, contents: &[u8]) -> &mut Self {
+        self.inner
+            .files
+            .insert(path.to_string(), VfsFile::Contents(contents.into()));
+        self
+    }
+
+    /// Add a file at `path` to the guest's read-only filesystem, whose
+    /// contents are read from `host_path` when the guest opens it.
+    pub fn host_file(&mut self, path: &str, host_path: impl AsRef<Path>) -> &mut Self {
+        self.inner.files.insert(
+            path.to_string(),
+            VfsFile::Host(host_path.as_ref().to_path_buf()),
+        );
+        self
+    }
+
+    /// Set the most verbose level at which the guest sends records from the
+    /// `log` facade to the host, where they are logged with the `guest`
+    /// target. Defaults to the host's `log::max_level()` when the
+    /// environment is built.
+    pub fn log_level(&mut self, level: LevelFilter) -> &mut Self {
+        self.inner.log_level = Some(level);
+        self
+    }
+
     /// Add a posix-style standard input.
     pub fn stdin(&mut self, reader: impl Read + 'a) -> &mut Self {
         self.read_fd(fileno::STDIN, BufReader::new(reader))
@@ -206,6 +311,20 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add a handler for the [Service] `S`. The guest can call it by invoking
+    /// `risc0_zkvm::rpc::call`, or the `call` function defined by
+    /// [rpc_service!](crate::rpc_service).
+    pub fn service<S: Service>(
+        &mut self,
+        handler: impl Fn(S::Request) -> Result<S::Response> + 'a,
+    ) -> &mut Self {
+        self.slice_io(
+            S::SYSCALL,
+            slice_io_from_fn(move |words: &[u32]| handle_call::<S>(words, &handler)),
+        );
+        self
+    }
+
     /// Add a callback handler for raw trace messages.
     pub fn trace_callback(
         &mut self,
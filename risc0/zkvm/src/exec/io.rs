@@ -31,7 +31,8 @@ use risc0_zkvm_platform::{
     memory::SYSTEM,
     syscall::{
         nr::{
-            SYS_CYCLE_COUNT, SYS_LOG, SYS_PANIC, SYS_RANDOM, SYS_READ, SYS_READ_AVAIL, SYS_WRITE,
+            SYS_CYCLE_COUNT, SYS_LOG, SYS_LOG_RECORD, SYS_PANIC, SYS_RANDOM, SYS_READ,
+            SYS_READ_AVAIL, SYS_WRITE,
         },
         reg_abi::{REG_A3, REG_A4, REG_A5},
         SyscallName,
@@ -216,6 +217,18 @@ impl<'a> PosixIo<'a> {
         self
     }
 
+    /// Returns an unused file descriptor that is at least `min`.
+    pub(crate) fn unused_fd(&self, min: u32) -> u32 {
+        (min..)
+            .find(|fd| !self.read_fds.contains_key(fd) && !self.write_fds.contains_key(fd))
+            .unwrap()
+    }
+
+    /// Removes a file descriptor for reading, returning whether it existed.
+    pub(crate) fn close_read_fd(&mut self, fd: u32) -> bool {
+        self.read_fds.remove(&fd).is_some()
+    }
+
     fn sys_read_avail(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
         let fd = ctx.load_register(REG_A3);
         let reader = self
@@ -366,6 +379,7 @@ impl<'a> Default for SyscallTable<'a> {
         };
         new.with_syscall(SYS_CYCLE_COUNT, syscalls::CycleCount)
             .with_syscall(SYS_LOG, syscalls::Log)
+            .with_syscall(SYS_LOG_RECORD, syscalls::LogRecord)
             .with_syscall(SYS_PANIC, syscalls::Panic)
             .with_syscall(SYS_RANDOM, syscalls::Random);
         new
@@ -384,12 +398,14 @@ pub(crate) mod syscalls {
     use std::{cmp::min, collections::HashMap, str::from_utf8};
 
     use anyhow::{bail, Result};
+    use log::{Level, LevelFilter, Record};
     use risc0_zkvm_platform::{
         syscall::reg_abi::{REG_A3, REG_A4},
         WORD_SIZE,
     };
 
     use super::{Syscall, SyscallContext};
+    use crate::serde::from_slice;
 
     pub(crate) struct CycleCount;
     impl Syscall for CycleCount {
@@ -428,6 +444,50 @@ pub(crate) mod syscalls {
         }
     }
 
+    /// Copies part of the input blob into the guest's `memory::INPUT` region,
+    /// which the guest does a page at a time. The guest passes the
+    /// word-aligned byte offset to start at in REG_A3, and gets back the
+    /// length of the blob in bytes.
+    pub(crate) struct InputRegion<'a>(pub &'a [u8]);
+    impl<'a> Syscall for InputRegion<'a> {
+        fn syscall(
+            &mut self,
+            _syscall: &str,
+            ctx: &mut dyn SyscallContext,
+            to_guest: &mut [u32],
+        ) -> Result<(u32, u32)> {
+            let offset = ctx.load_register(REG_A3) as usize;
+            if offset % WORD_SIZE != 0 || offset > self.0.len() {
+                bail!(
+                    "Invalid input region offset {offset} for an input of {} bytes",
+                    self.0.len()
+                );
+            }
+            let src = &self.0[offset..];
+            let nbytes = min(src.len(), to_guest.len() * WORD_SIZE);
+            let to_guest_u8s: &mut [u8] = bytemuck::cast_slice_mut(to_guest);
+            to_guest_u8s[..nbytes].clone_from_slice(&src[..nbytes]);
+            Ok((self.0.len() as u32, 0))
+        }
+    }
+
+    /// Sends the public input to the guest, which gets back its length in
+    /// bytes.
+    pub(crate) struct PublicInput(pub Vec<u8>);
+    impl Syscall for PublicInput {
+        fn syscall(
+            &mut self,
+            _syscall: &str,
+            _ctx: &mut dyn SyscallContext,
+            to_guest: &mut [u32],
+        ) -> Result<(u32, u32)> {
+            let nbytes = min(self.0.len(), to_guest.len() * WORD_SIZE);
+            let to_guest_u8s: &mut [u8] = bytemuck::cast_slice_mut(to_guest);
+            to_guest_u8s[..nbytes].clone_from_slice(&self.0[..nbytes]);
+            Ok((self.0.len() as u32, 0))
+        }
+    }
+
     pub(crate) struct Log;
     impl Syscall for Log {
         fn syscall(
@@ -445,6 +505,65 @@ pub(crate) mod syscalls {
         }
     }
 
+    /// Tells the guest the most verbose level it should send log records at.
+    pub(crate) struct LogLevel(pub LevelFilter);
+    impl Syscall for LogLevel {
+        fn syscall(
+            &mut self,
+            _syscall: &str,
+            _ctx: &mut dyn SyscallContext,
+            _to_guest: &mut [u32],
+        ) -> Result<(u32, u32)> {
+            Ok((self.0 as u32, 0))
+        }
+    }
+
+    /// Passes a log record from the guest's `log` facade on to the host's,
+    /// with the `guest` target.
+    pub(crate) struct LogRecord;
+    impl Syscall for LogRecord {
+        fn syscall(
+            &mut self,
+            _syscall: &str,
+            ctx: &mut dyn SyscallContext,
+            _to_guest: &mut [u32],
+        ) -> Result<(u32, u32)> {
+            let buf_ptr = ctx.load_register(REG_A3);
+            let buf_words = ctx.load_register(REG_A4);
+            let words: Vec<u32> = (0..buf_words)
+                .map(|i| ctx.load_u32(buf_ptr + i * WORD_SIZE as u32))
+                .collect();
+            let (level, module_path, file, line, msg): (
+                u32,
+                Option<String>,
+                Option<String>,
+                Option<u32>,
+                String,
+            ) = from_slice(&words)?;
+            let level = match level {
+                1 => Level::Error,
+                2 => Level::Warn,
+                3 => Level::Info,
+                4 => Level::Debug,
+                5 => Level::Trace,
+                _ => bail!("Invalid guest log level: {level}"),
+            };
+            if level <= log::max_level() {
+                log::logger().log(
+                    &Record::builder()
+                        .level(level)
+                        .target("guest")
+                        .module_path(module_path.as_deref())
+                        .file(file.as_deref())
+                        .line(line)
+                        .args(format_args!("R0VM[{}] {msg}", ctx.get_cycle()))
+                        .build(),
+                );
+            }
+            Ok((0, 0))
+        }
+    }
+
     pub(crate) struct Panic;
     impl Syscall for Panic {
         fn syscall(
@@ -24,6 +24,7 @@ mod monitor;
 pub(crate) mod profiler;
 #[cfg(test)]
 mod tests;
+mod vfs;
 
 use std::{array, cell::RefCell, fmt::Debug, io::Write, mem::take, rc::Rc};
 
@@ -32,7 +33,7 @@ use num_bigint::BigUint;
 use num_traits::Zero;
 use risc0_zkp::{
     core::{
-        digest::{DIGEST_BYTES, DIGEST_WORDS},
+        digest::{Digest, DIGEST_BYTES, DIGEST_WORDS},
         hash::sha::{BLOCK_BYTES, BLOCK_WORDS},
         log2_ceil,
     },
@@ -55,7 +56,7 @@ use self::monitor::MemoryMonitor;
 use crate::{
     align_up,
     opcode::{MajorType, OpCode},
-    receipt::ExitCode,
+    receipt::{public_input_digest, ExitCode},
     Loader, MemoryImage, Program, Segment, SegmentRef, Session, SimpleSegmentRef,
 };
 
@@ -80,6 +81,7 @@ pub struct Executor<'a> {
     segments: Vec<Box<dyn SegmentRef>>,
     insn_counter: u32,
     split_insn: Option<u32>,
+    input_digest: Digest,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -136,6 +138,7 @@ impl<'a> Executor<'a> {
         let loader = Loader::new();
         let init_cycles = loader.init_cycles();
         let fini_cycles = loader.fini_cycles();
+        let input_digest = public_input_digest(&env.public_input);
 
         Self {
             env,
@@ -149,6 +152,7 @@ impl<'a> Executor<'a> {
             segments: Vec::new(),
             insn_counter: 0,
             split_insn: None,
+            input_digest,
         }
     }
 
@@ -196,6 +200,7 @@ impl<'a> Executor<'a> {
                         faults,
                         syscalls,
                         exit_code,
+                        self.input_digest,
                         self.split_insn,
                         log2_ceil(total_cycles.next_power_of_two()),
                         self.segments
@@ -224,10 +229,11 @@ impl<'a> Executor<'a> {
         };
 
         let exit_code = run_loop()?;
-        Ok(Session::new(
+        Ok(Session::new_with_input(
             take(&mut self.segments),
             journal.buf.take(),
             exit_code,
+            self.env.public_input.clone(),
         ))
     }
 
@@ -405,8 +411,12 @@ impl<'a> Executor<'a> {
     fn ecall_input(&mut self) -> Result<OpCodeResult> {
         log::debug!("ecall(input)");
         let in_addr = self.monitor.load_register(REG_A0);
-        self.monitor
+        let digest = self
+            .monitor
             .load_array::<{ DIGEST_WORDS * WORD_SIZE }>(in_addr);
+        if digest.as_slice() != self.input_digest.as_bytes() {
+            bail!("Guest public input digest does not match the public input");
+        }
         Ok(OpCodeResult::new(self.pc + WORD_SIZE as u32, None, 0, None))
     }
 
@@ -15,15 +15,17 @@
 use std::{collections::BTreeMap, io::Cursor, str::from_utf8, sync::Mutex};
 
 use risc0_zkvm_methods::{
-    multi_test::{MultiTestSpec, SYS_MULTI_TEST},
-    HELLO_COMMIT_ELF, MULTI_TEST_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
+    multi_test::{AddOne, EcdsaCurve, MultiTestSpec, TaggedInput, SYS_MULTI_TEST},
+    HELLO_COMMIT_ELF, MULTI_TEST_ELF, RECLAIM_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
 };
-use risc0_zkvm_platform::{fileno, PAGE_SIZE, WORD_SIZE};
+use risc0_zkvm_platform::{fileno, memory, syscall::bigint, PAGE_SIZE, WORD_SIZE};
 use test_log::test;
 
 use super::{Executor, ExecutorEnv, TraceEvent};
 use crate::{
-    serde::{from_slice, to_vec},
+    merkle::{hash_leaf, MerkleTree},
+    serde::{from_slice, from_slice_tagged, to_vec, to_vec_tagged},
+    sha::Digest,
     testutils, ExitCode, MemoryImage, Program,
 };
 
@@ -173,6 +175,178 @@ fn bigint_accel() {
     }
 }
 
+#[test]
+fn bigint_field() {
+    use num_bigint::BigUint;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::guest::bigint::{ec::Secp256k1Base, FieldParams};
+
+    let p = BigUint::from_slice(&Secp256k1Base::MODULUS.0);
+    let words = |value: &BigUint| {
+        let mut words = value.to_u32_digits();
+        words.resize(bigint::WIDTH_WORDS, 0);
+        <[u32; bigint::WIDTH_WORDS]>::try_from(words).unwrap()
+    };
+    let cases = testutils::generate_bigint_test_cases(&mut StdRng::seed_from_u64(0), 4);
+    for case in cases {
+        let input = to_vec(&MultiTestSpec::BigIntField {
+            x: case.x,
+            y: case.y,
+        })
+        .unwrap();
+        let env = ExecutorEnv::builder().add_input(&input).build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        let (mul, pow, inv): (
+            [u32; bigint::WIDTH_WORDS],
+            [u32; bigint::WIDTH_WORDS],
+            Option<[u32; bigint::WIDTH_WORDS]>,
+        ) = from_slice(&session.journal).unwrap();
+
+        let x = BigUint::from_slice(&case.x) % &p;
+        let y = BigUint::from_slice(&case.y) % &p;
+        assert_eq!(mul, words(&(&x * &y % &p)));
+        assert_eq!(pow, words(&x.modpow(&y, &p)));
+        let expected_inv = (x != BigUint::default()).then(|| words(&x.modpow(&(&p - 2u32), &p)));
+        assert_eq!(inv, expected_inv);
+    }
+}
+
+#[test]
+fn ecdsa_verify() {
+    use k256::ecdsa::signature::{Signer, Verifier};
+    use num_bigint::BigUint;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use crate::guest::bigint::{ec::Secp256k1Scalar, FieldParams};
+
+    let run = |curve, public_key: &[u8], message: &[u8], signature: &[u8]| {
+        let input = to_vec(&MultiTestSpec::EcdsaVerify {
+            curve,
+            public_key: public_key.to_vec(),
+            message: message.to_vec(),
+            signature: signature.to_vec(),
+        })
+        .unwrap();
+        let env = ExecutorEnv::builder().add_input(&input).build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        from_slice::<bool, _>(&session.journal).unwrap()
+    };
+
+    let message = b"message to sign";
+    let key = k256::ecdsa::SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+    let public_key = key.verifying_key().to_encoded_point(true);
+    let signature: k256::ecdsa::Signature = key.sign(message);
+    let signature = signature.to_bytes();
+    assert!(run(
+        EcdsaCurve::Secp256k1,
+        public_key.as_bytes(),
+        message,
+        &signature
+    ));
+    assert!(!run(
+        EcdsaCurve::Secp256k1,
+        public_key.as_bytes(),
+        b"other message",
+        &signature
+    ));
+
+    let key = p256::ecdsa::SigningKey::from_bytes(&[0x44; 32].into()).unwrap();
+    let public_key = key.verifying_key().to_encoded_point(false);
+    let signature: p256::ecdsa::Signature = key.sign(message);
+    let signature = signature.to_bytes();
+    assert!(run(
+        EcdsaCurve::P256,
+        public_key.as_bytes(),
+        message,
+        &signature
+    ));
+    assert!(!run(
+        EcdsaCurve::P256,
+        public_key.as_bytes(),
+        b"other message",
+        &signature
+    ));
+
+    // Compare against k256 on random keys and messages. Unlike k256,
+    // `verify_ecdsa` also accepts signatures with `s` replaced by `n - s`.
+    let n = BigUint::from_slice(&Secp256k1Scalar::MODULUS.0);
+    let be_bytes = |value: &BigUint| {
+        let bytes = value.to_bytes_be();
+        let mut padded = [0; 32];
+        padded[32 - bytes.len()..].copy_from_slice(&bytes);
+        padded
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..3 {
+        let key = k256::ecdsa::SigningKey::random(&mut rng);
+        let verifying_key = key.verifying_key();
+        let public_key = verifying_key.to_encoded_point(true);
+        let mut message = [0; 32];
+        rng.fill_bytes(&mut message);
+        let signature: k256::ecdsa::Signature = key.sign(&message);
+        let mut other_message = message;
+        other_message[0] ^= 1;
+        for message in [message, other_message] {
+            assert_eq!(
+                run(
+                    EcdsaCurve::Secp256k1,
+                    public_key.as_bytes(),
+                    &message,
+                    &signature.to_bytes()
+                ),
+                verifying_key.verify(&message, &signature).is_ok()
+            );
+        }
+
+        let bytes = signature.to_bytes();
+        let (r, s) = bytes.split_at(32);
+        let high_s = [r, &be_bytes(&(&n - BigUint::from_bytes_be(s)))[..]].concat();
+        let high_s = k256::ecdsa::Signature::from_slice(&high_s).unwrap();
+        assert!(verifying_key.verify(&message, &high_s).is_err());
+        assert!(run(
+            EcdsaCurve::Secp256k1,
+            public_key.as_bytes(),
+            &message,
+            &high_s.to_bytes()
+        ));
+    }
+
+    // `r` and `s` must both be less than the order of the curve.
+    let key = k256::ecdsa::SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+    let public_key = key.verifying_key().to_encoded_point(true);
+    let signature: k256::ecdsa::Signature = key.sign(message);
+    let bytes = signature.to_bytes();
+    let (r, s) = bytes.split_at(32);
+    for signature in [
+        [&be_bytes(&n)[..], s].concat(),
+        [r, &be_bytes(&n)[..]].concat(),
+        [&[0xff; 32][..], s].concat(),
+        [r, &[0xff; 32][..]].concat(),
+    ] {
+        assert!(!run(
+            EcdsaCurve::Secp256k1,
+            public_key.as_bytes(),
+            message,
+            &signature
+        ));
+    }
+
+    // With the public key `-G`, `G + Q` is the point at infinity.
+    let key = k256::ecdsa::SigningKey::from_bytes(&be_bytes(&(&n - 1u32)).into()).unwrap();
+    let public_key = key.verifying_key().to_encoded_point(true);
+    let signature: k256::ecdsa::Signature = key.sign(message);
+    assert!(key.verifying_key().verify(message, &signature).is_ok());
+    assert!(run(
+        EcdsaCurve::Secp256k1,
+        public_key.as_bytes(),
+        message,
+        &signature.to_bytes()
+    ));
+}
+
 #[test]
 fn sha_cycle_count() {
     let input = to_vec(&MultiTestSpec::ShaCycleCount).unwrap();
@@ -338,6 +512,213 @@ fn slice_io() {
     run(b"0000");
 }
 
+#[test]
+fn rpc() {
+    let spec = to_vec(&MultiTestSpec::Rpc {
+        values: vec![1, 2, 3],
+    })
+    .unwrap();
+    let env = ExecutorEnv::builder()
+        .add_input(&spec)
+        .service::<AddOne>(|values| Ok(values.iter().map(|x| x + 1).collect()))
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let reply: Vec<u32> = from_slice(&session.journal).unwrap();
+    assert_eq!(reply, vec![2, 3, 4]);
+}
+
+#[test]
+fn rpc_handler_error() {
+    let spec = to_vec(&MultiTestSpec::Rpc { values: vec![] }).unwrap();
+    let env = ExecutorEnv::builder()
+        .add_input(&spec)
+        .service::<AddOne>(|_| anyhow::bail!("no values"))
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let err = exec.run().err().unwrap();
+    assert!(err.to_string().contains("no values"));
+}
+
+#[test]
+fn input_region() {
+    let blob: Vec<u8> = (0..10_000).map(|x| x as u8).collect();
+    let spec = to_vec(&MultiTestSpec::InputRegion {
+        start: 4097,
+        end: 5003,
+    })
+    .unwrap();
+    let env = ExecutorEnv::builder()
+        .add_input(&spec)
+        .input_region(&blob)
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let (all, part): (Vec<u8>, Vec<u8>) = from_slice(&session.journal).unwrap();
+    assert_eq!(all, blob);
+    assert_eq!(part, &blob[4097..5003]);
+}
+
+#[test]
+fn heap_reclaim() {
+    // 100 MB are allocated in total, more than the whole heap, but no more
+    // than 3 MB at a time.
+    let (len, live, iters) = (1usize << 20, 2usize, 100usize);
+    assert!(len * iters > memory::HEAP.len_bytes());
+    let env = ExecutorEnv::builder()
+        .add_input(&to_vec(&(len, live, iters)).unwrap())
+        .build();
+    let mut exec = Executor::from_elf(env, RECLAIM_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+}
+
+#[test]
+fn exit_code() {
+    let spec = to_vec(&MultiTestSpec::Exit { code: 7 }).unwrap();
+    let env = ExecutorEnv::builder().add_input(&spec).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(7));
+    let code: u8 = from_slice(&session.journal).unwrap();
+    assert_eq!(code, 7);
+}
+
+#[test]
+fn fallible_main() {
+    let spec = to_vec(&MultiTestSpec::Reject {
+        reason: "bad input".into(),
+    })
+    .unwrap();
+    let env = ExecutorEnv::builder().add_input(&spec).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(1));
+    let reason: String = from_slice(&session.journal).unwrap();
+    assert_eq!(reason, "bad input");
+}
+
+#[test]
+fn try_read() {
+    let run = |input: &[u32]| {
+        let env = ExecutorEnv::builder()
+            .add_input(&to_vec(&MultiTestSpec::TryRead).unwrap())
+            .add_input(input)
+            .build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        exec.run().unwrap()
+    };
+
+    let session = run(&to_vec(&(7u32, "seven")).unwrap());
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let output: (u32, String) = from_slice(&session.journal).unwrap();
+    assert_eq!(output, (7, "seven".into()));
+
+    // A string whose length runs past the end of the input.
+    let session = run(&[7, 100, 0]);
+    assert_eq!(session.exit_code, ExitCode::Halted(1));
+    let err: String = from_slice(&session.journal).unwrap();
+    assert_eq!(
+        err,
+        "Unexpected end during deserialization (after reading 2 words)"
+    );
+}
+
+#[test]
+fn read_tagged() {
+    let inputs = vec![
+        TaggedInput::Number(7),
+        TaggedInput::Name("seven".into()),
+        TaggedInput::Point { x: -1, y: None },
+        TaggedInput::Point { x: 3, y: Some(4) },
+    ];
+    let env = ExecutorEnv::builder()
+        .add_input(&to_vec(&MultiTestSpec::ReadTagged).unwrap())
+        .add_input(&to_vec_tagged(&inputs).unwrap())
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let output: Vec<TaggedInput> = from_slice_tagged(&session.journal).unwrap();
+    assert_eq!(output, inputs);
+}
+
+#[test]
+fn merkle_root() {
+    let leaves: Vec<_> = (0..13u32).map(|i| hash_leaf(i.to_le_bytes())).collect();
+    let tree = MerkleTree::new(leaves.clone());
+    let proof = tree.prove_multi(&[5, 5, 6]);
+    let run = |leaves: Vec<(usize, Digest)>| {
+        let spec = MultiTestSpec::MerkleRoot {
+            proof: proof.clone(),
+            leaves,
+        };
+        let env = ExecutorEnv::builder()
+            .add_input(&to_vec(&spec).unwrap())
+            .build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        from_slice::<Option<Digest>, _>(&session.journal).unwrap()
+    };
+
+    assert_eq!(
+        run(vec![(5, leaves[5]), (5, leaves[5]), (6, leaves[6])]),
+        Some(tree.root())
+    );
+    // The guest rejects a duplicate index with a different digest, rather
+    // than using whichever digest sorts first.
+    assert_eq!(
+        run(vec![(5, leaves[5]), (5, leaves[4]), (6, leaves[6])]),
+        None
+    );
+    assert_eq!(
+        run(vec![(5, leaves[4]), (5, leaves[5]), (6, leaves[6])]),
+        None
+    );
+}
+
+#[test]
+fn public_input() {
+    let spec = to_vec(&MultiTestSpec::PublicInput).unwrap();
+    let env = ExecutorEnv::builder()
+        .add_input(&spec)
+        .public_input(b"public bytes")
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.input, b"public bytes");
+    let len: u32 = from_slice(&session.journal).unwrap();
+    assert_eq!(len, 12);
+}
+
+#[test]
+fn read_file() {
+    let host_file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(host_file.path(), "from the host").unwrap();
+
+    let read_file = |path: &str| {
+        let spec = to_vec(&MultiTestSpec::ReadFile { path: path.into() }).unwrap();
+        let env = ExecutorEnv::builder()
+            .add_input(&spec)
+            .file("/etc/config.toml", b"key = 1")
+            .host_file("/data/host.txt", host_file.path())
+            .build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        let result: (Option<u32>, Option<String>) = from_slice(&session.journal).unwrap();
+        result
+    };
+
+    assert_eq!(
+        read_file("/etc/config.toml"),
+        (Some(7), Some("key = 1".into()))
+    );
+    assert_eq!(
+        read_file("/data/host.txt"),
+        (Some(13), Some("from the host".into()))
+    );
+    assert_eq!(read_file("/missing"), (None, None));
+}
+
 // Check that a compliant host will fault.
 #[test]
 fn fail() {
@@ -0,0 +1,134 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A read-only virtual filesystem for the guest.
+//!
+//! Each path in the filesystem is backed either by a buffer or by a file on
+//! the host, which is read when the guest opens it. Opening a file registers
+//! its contents as a read file descriptor with [PosixIo], so that the guest
+//! reads it with the same `SYS_READ` system call as any other descriptor.
+
+use std::{cell::RefCell, collections::BTreeMap, fs, io::Cursor, path::PathBuf, rc::Rc};
+
+use anyhow::{bail, Context, Result};
+use risc0_zkvm_platform::syscall::{
+    nr::{SYS_CLOSE, SYS_OPEN, SYS_STAT},
+    reg_abi::{REG_A3, REG_A4},
+};
+
+use super::io::{PosixIo, Syscall, SyscallContext};
+
+/// Returned to the guest when a path does not exist.
+const NOT_FOUND: u32 = u32::MAX;
+
+/// File descriptors for opened files are allocated from here up, so that
+/// they don't collide with descriptors registered by the user.
+const FIRST_FD: u32 = 1024;
+
+/// The contents of a path in the virtual filesystem.
+#[derive(Clone)]
+pub(crate) enum VfsFile {
+    Contents(Rc<[u8]>),
+    Host(PathBuf),
+}
+
+impl VfsFile {
+    fn len(&self) -> Result<u32> {
+        let len = match self {
+            VfsFile::Contents(contents) => contents.len() as u64,
+            VfsFile::Host(path) => fs::metadata(path)
+                .with_context(|| format!("Failed to stat {}", path.display()))?
+                .len(),
+        };
+        u32::try_from(len).context("File is too large for the guest")
+    }
+
+    fn contents(&self) -> Result<Rc<[u8]>> {
+        match self {
+            VfsFile::Contents(contents) => Ok(contents.clone()),
+            VfsFile::Host(path) => Ok(fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+                .into()),
+        }
+    }
+}
+
+/// Handles `SYS_OPEN`, `SYS_STAT` and `SYS_CLOSE` for the files in the
+/// virtual filesystem.
+pub(crate) struct Vfs<'a> {
+    files: BTreeMap<String, VfsFile>,
+    io: Rc<RefCell<PosixIo<'a>>>,
+}
+
+impl<'a> Vfs<'a> {
+    pub(crate) fn new(files: BTreeMap<String, VfsFile>, io: Rc<RefCell<PosixIo<'a>>>) -> Self {
+        Self { files, io }
+    }
+
+    fn lookup(&self, ctx: &mut dyn SyscallContext) -> Result<Option<&VfsFile>> {
+        let path_ptr = ctx.load_register(REG_A3);
+        let path_len = ctx.load_register(REG_A4);
+        let path = String::from_utf8(ctx.load_region(path_ptr, path_len))?;
+        log::debug!("Looking up {path} in the virtual filesystem");
+        Ok(self.files.get(&path))
+    }
+
+    fn sys_open(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
+        let file = match self.lookup(ctx)? {
+            Some(file) => file,
+            None => return Ok((NOT_FOUND, 0)),
+        };
+        let contents = file.contents()?;
+        let len = u32::try_from(contents.len()).context("File is too large for the guest")?;
+        let mut io = self.io.borrow_mut();
+        let fd = io.unused_fd(FIRST_FD);
+        io.with_read_fd(fd, Cursor::new(contents));
+        Ok((fd, len))
+    }
+
+    fn sys_stat(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
+        match self.lookup(ctx)? {
+            Some(file) => Ok((0, file.len()?)),
+            None => Ok((NOT_FOUND, 0)),
+        }
+    }
+
+    fn sys_close(&mut self, ctx: &mut dyn SyscallContext) -> Result<(u32, u32)> {
+        let fd = ctx.load_register(REG_A3);
+        if fd < FIRST_FD || !self.io.borrow_mut().close_read_fd(fd) {
+            bail!("Bad file descriptor {fd} for close");
+        }
+        Ok((0, 0))
+    }
+}
+
+impl<'a> Syscall for Rc<RefCell<Vfs<'a>>> {
+    fn syscall(
+        &mut self,
+        syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let mut vfs = self.borrow_mut();
+        if syscall == SYS_OPEN.as_str() {
+            vfs.sys_open(ctx)
+        } else if syscall == SYS_STAT.as_str() {
+            vfs.sys_stat(ctx)
+        } else if syscall == SYS_CLOSE.as_str() {
+            vfs.sys_close(ctx)
+        } else {
+            bail!("Unknown syscall {syscall}")
+        }
+    }
+}
@@ -12,6 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! The guest heap allocator.
+//!
+//! By default, memory is handed out from [memory::HEAP] by a bump pointer and
+//! is never freed, which is as cheap as allocation gets but means that a guest
+//! which keeps allocating temporary buffers eventually runs out of memory even
+//! if it only holds on to a little of it at a time. Enabling the
+//! `heap-reclaim` feature replaces it with an allocator that reuses freed
+//! blocks, at the cost of some cycles per allocation.
+
 use core::{
     alloc::{GlobalAlloc, Layout},
     cell::UnsafeCell,
@@ -39,6 +48,100 @@ unsafe impl GlobalAlloc for BumpPointerAlloc {
     }
 }
 
-#[cfg(target_os = "zkvm")]
+#[cfg(all(target_os = "zkvm", not(feature = "heap-reclaim")))]
 #[global_allocator]
 static HEAP: BumpPointerAlloc = BumpPointerAlloc;
+
+/// The number of size classes: one for each power of two block size that fits
+/// in the address space.
+const NUM_CLASSES: usize = usize::BITS as usize;
+
+/// A freed block, linked into the free list for its size class.
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+/// An allocator that keeps freed blocks on free lists segregated by size class
+/// and hands them out again to later allocations of the same class. Fresh
+/// blocks are carved out of [memory::HEAP] with `sys_alloc_words`, just like
+/// [BumpPointerAlloc].
+///
+/// Every block is a power of two of at least one word, so at most half of a
+/// block is wasted. Blocks are never split or coalesced: a block freed by one
+/// size class can only be reused by the same class, which keeps allocation and
+/// deallocation down to a handful of instructions.
+struct ReclaimingAlloc {
+    free: UnsafeCell<[*mut FreeBlock; NUM_CLASSES]>,
+}
+
+// SAFETY: The guest is single threaded.
+unsafe impl Sync for ReclaimingAlloc {}
+
+impl ReclaimingAlloc {
+    const fn new() -> Self {
+        Self {
+            free: UnsafeCell::new([core::ptr::null_mut(); NUM_CLASSES]),
+        }
+    }
+
+    /// Returns the size class for `layout`, whose blocks are `1 << class`
+    /// bytes, or `None` if a block of that size would not fit in the heap.
+    fn class(layout: &Layout) -> Option<usize> {
+        let size = layout.size().max(layout.align()).max(WORD_SIZE);
+        size.checked_next_power_of_two()
+            .filter(|block| *block <= memory::HEAP.len_bytes())
+            .map(|block| block.trailing_zeros() as usize)
+    }
+}
+
+#[cfg(target_os = "zkvm")]
+unsafe impl GlobalAlloc for ReclaimingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let class = match Self::class(&layout) {
+            Some(class) => class,
+            None => return core::ptr::null_mut(),
+        };
+        let head = &mut (*self.free.get())[class];
+        // Only the head of the list is considered; if it isn't aligned enough
+        // for this allocation, a fresh block is used instead.
+        if !head.is_null() && *head as usize % layout.align() == 0 {
+            let block = *head;
+            *head = (*block).next;
+            return block as *mut u8;
+        }
+
+        // `sys_alloc_words` only guarantees word alignment, so skip ahead to
+        // the requested alignment first.
+        let next = syscall::sys_alloc_words(0) as usize;
+        let padding = next.wrapping_neg() % layout.align().max(WORD_SIZE);
+        syscall::sys_alloc_words(padding / WORD_SIZE);
+        syscall::sys_alloc_words((1 << class) / WORD_SIZE) as *mut u8
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Only layouts that `alloc` served get here, so they have a class.
+        let head = &mut (*self.free.get())[Self::class(&layout).unwrap()];
+        let block = ptr as *mut FreeBlock;
+        (*block).next = *head;
+        *head = block;
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        if Self::class(&new_layout) == Self::class(&layout) {
+            return ptr;
+        }
+        let new_ptr = self.alloc(new_layout);
+        if new_ptr.is_null() {
+            // The original block is left untouched, as `realloc` requires.
+            return new_ptr;
+        }
+        core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+        self.dealloc(ptr, layout);
+        new_ptr
+    }
+}
+
+#[cfg(all(target_os = "zkvm", feature = "heap-reclaim"))]
+#[global_allocator]
+static HEAP: ReclaimingAlloc = ReclaimingAlloc::new();
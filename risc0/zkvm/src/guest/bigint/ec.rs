@@ -0,0 +1,494 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Elliptic curve arithmetic and ECDSA verification for short Weierstrass
+//! curves, with [Secp256k1] and [P256] built in.
+//!
+//! ```ignore
+//! use risc0_zkvm::guest::bigint::ec::{verify_ecdsa, AffinePoint, Secp256k1, Signature};
+//!
+//! let public_key = AffinePoint::<Secp256k1>::from_sec1(&public_key_bytes).unwrap();
+//! let signature = Signature::from_bytes(&signature_bytes).unwrap();
+//! assert!(verify_ecdsa(&public_key, message, &signature));
+//! ```
+//!
+//! Points are added in Jacobian coordinates, which need no field inversions,
+//! so verifying a signature costs a few thousand BigInt accelerator calls.
+//! None of these operations are constant time, which doesn't matter for
+//! verification but means they shouldn't be used with secret scalars outside
+//! the zkVM.
+
+use core::fmt::Debug;
+
+use risc0_zkvm_platform::syscall::bigint::WIDTH_BYTES;
+
+use super::{FieldElement, FieldParams, U256};
+use crate::sha::{Impl, Sha256};
+
+/// A short Weierstrass curve `y^2 = x^3 + ax + b` of prime order.
+pub trait Curve: Copy + Debug + Eq + 'static {
+    /// The field of coordinates.
+    type Base: FieldParams;
+
+    /// The field of scalars, whose modulus is the order of the curve.
+    type Scalar: FieldParams;
+
+    /// The coefficient `a`.
+    const A: FieldElement<Self::Base>;
+
+    /// The coefficient `b`.
+    const B: FieldElement<Self::Base>;
+
+    /// The coordinates of the generator.
+    const GENERATOR: (FieldElement<Self::Base>, FieldElement<Self::Base>);
+}
+
+/// A point on the curve `C` other than the point at infinity.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AffinePoint<C: Curve> {
+    x: FieldElement<C::Base>,
+    y: FieldElement<C::Base>,
+}
+
+impl<C: Curve> AffinePoint<C> {
+    /// Returns the point with the given coordinates, or `None` if it is not on
+    /// the curve.
+    pub fn new(x: FieldElement<C::Base>, y: FieldElement<C::Base>) -> Option<Self> {
+        (y.square() == Self::rhs(&x)).then_some(Self { x, y })
+    }
+
+    /// Returns the generator of the curve.
+    pub fn generator() -> Self {
+        let (x, y) = C::GENERATOR;
+        Self { x, y }
+    }
+
+    // Returns x^3 + ax + b.
+    fn rhs(x: &FieldElement<C::Base>) -> FieldElement<C::Base> {
+        (x.square() + C::A) * *x + C::B
+    }
+
+    /// Decode a point in the compressed or uncompressed SEC1 encoding, or
+    /// return `None` if it is malformed or not on the curve.
+    pub fn from_sec1(bytes: &[u8]) -> Option<Self> {
+        let coordinate = |bytes: &[u8]| FieldElement::from_be_bytes(bytes.try_into().ok()?);
+        match bytes.split_first()? {
+            (4, rest) if rest.len() == 2 * WIDTH_BYTES => {
+                let (x, y) = rest.split_at(WIDTH_BYTES);
+                Self::new(coordinate(x)?, coordinate(y)?)
+            }
+            (tag @ (2 | 3), x) => {
+                let x = coordinate(x)?;
+                let y = Self::rhs(&x).sqrt()?;
+                let y = if y.value().bit(0) == (*tag == 3) {
+                    y
+                } else {
+                    -y
+                };
+                Some(Self { x, y })
+            }
+            _ => None,
+        }
+    }
+
+    /// Encode the point in the uncompressed SEC1 encoding.
+    pub fn to_uncompressed(&self) -> [u8; 2 * WIDTH_BYTES + 1] {
+        let mut bytes = [4; 2 * WIDTH_BYTES + 1];
+        bytes[1..1 + WIDTH_BYTES].copy_from_slice(&self.x.to_be_bytes());
+        bytes[1 + WIDTH_BYTES..].copy_from_slice(&self.y.to_be_bytes());
+        bytes
+    }
+
+    /// Returns the x coordinate.
+    pub fn x(&self) -> &FieldElement<C::Base> {
+        &self.x
+    }
+
+    /// Returns the y coordinate.
+    pub fn y(&self) -> &FieldElement<C::Base> {
+        &self.y
+    }
+}
+
+/// A point on the curve `C` in Jacobian coordinates `(X, Y, Z)`, which
+/// represent the affine point `(X / Z^2, Y / Z^3)`, or the point at infinity
+/// if `Z` is zero.
+#[derive(Clone, Copy, Debug)]
+pub struct JacobianPoint<C: Curve> {
+    x: FieldElement<C::Base>,
+    y: FieldElement<C::Base>,
+    z: FieldElement<C::Base>,
+}
+
+impl<C: Curve> JacobianPoint<C> {
+    /// The point at infinity.
+    pub const IDENTITY: Self = Self {
+        x: FieldElement::ONE,
+        y: FieldElement::ONE,
+        z: FieldElement::ZERO,
+    };
+
+    /// Returns whether this is the point at infinity.
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Returns this point in affine coordinates, or `None` if it is the point
+    /// at infinity.
+    pub fn to_affine(&self) -> Option<AffinePoint<C>> {
+        let z_inv = self.z.inv()?;
+        let z_inv2 = z_inv.square();
+        Some(AffinePoint {
+            x: self.x * z_inv2,
+            y: self.y * z_inv2 * z_inv,
+        })
+    }
+
+    /// Returns twice this point.
+    pub fn double(&self) -> Self {
+        if self.is_identity() || self.y.is_zero() {
+            return Self::IDENTITY;
+        }
+        let xx = self.x.square();
+        let yy = self.y.square();
+        let zz = self.z.square();
+        let s = (self.x * yy).double().double();
+        let m = xx.double() + xx + C::A * zz.square();
+        let x = m.square() - s.double();
+        let y = m * (s - x) - yy.square().double().double().double();
+        let z = (self.y * self.z).double();
+        Self { x, y, z }
+    }
+
+    /// Returns the sum of this point and `rhs`.
+    pub fn add(&self, rhs: &Self) -> Self {
+        if self.is_identity() {
+            return *rhs;
+        }
+        if rhs.is_identity() {
+            return *self;
+        }
+        let z1z1 = self.z.square();
+        let z2z2 = rhs.z.square();
+        let u1 = self.x * z2z2;
+        let u2 = rhs.x * z1z1;
+        let s1 = self.y * rhs.z * z2z2;
+        let s2 = rhs.y * self.z * z1z1;
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double()
+            } else {
+                Self::IDENTITY
+            };
+        }
+        let h = u2 - u1;
+        let r = s2 - s1;
+        let hh = h.square();
+        let hhh = h * hh;
+        let v = u1 * hh;
+        let x = r.square() - hhh - v.double();
+        let y = r * (v - x) - s1 * hhh;
+        let z = self.z * rhs.z * h;
+        Self { x, y, z }
+    }
+
+    /// Returns this point multiplied by `scalar`.
+    pub fn mul(&self, scalar: &U256) -> Self {
+        let mut result = Self::IDENTITY;
+        for i in (0..scalar.bits()).rev() {
+            result = result.double();
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+}
+
+impl<C: Curve> From<AffinePoint<C>> for JacobianPoint<C> {
+    fn from(point: AffinePoint<C>) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+            z: FieldElement::ONE,
+        }
+    }
+}
+
+/// An ECDSA signature on the curve `C`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Signature<C: Curve> {
+    r: FieldElement<C::Scalar>,
+    s: FieldElement<C::Scalar>,
+}
+
+impl<C: Curve> Signature<C> {
+    /// Returns the signature `(r, s)`, or `None` if either is zero.
+    pub fn new(r: FieldElement<C::Scalar>, s: FieldElement<C::Scalar>) -> Option<Self> {
+        (!r.is_zero() && !s.is_zero()).then_some(Self { r, s })
+    }
+
+    /// Decode the 64 byte encoding of big-endian `r` followed by big-endian
+    /// `s`, or return `None` if it is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 2 * WIDTH_BYTES {
+            return None;
+        }
+        let (r, s) = bytes.split_at(WIDTH_BYTES);
+        Self::new(
+            FieldElement::from_be_bytes(r.try_into().unwrap())?,
+            FieldElement::from_be_bytes(s.try_into().unwrap())?,
+        )
+    }
+}
+
+/// Verify an ECDSA signature over the SHA-256 of `message`.
+///
+/// Both `(r, s)` and `(r, n - s)` are accepted, as with the `p256` crate. The
+/// `k256` crate instead only accepts signatures with `s <= n / 2`, so guests
+/// that need to match its behavior on secp256k1 must reject high `s` values
+/// themselves.
+pub fn verify_ecdsa<C: Curve>(
+    public_key: &AffinePoint<C>,
+    message: &[u8],
+    signature: &Signature<C>,
+) -> bool {
+    let digest = Impl::hash_bytes(message);
+    verify_ecdsa_prehash(public_key, digest.as_bytes().try_into().unwrap(), signature)
+}
+
+/// Verify an ECDSA signature over a message whose 256-bit hash is `prehash`.
+///
+/// Like [verify_ecdsa], this accepts signatures with a high `s`.
+pub fn verify_ecdsa_prehash<C: Curve>(
+    public_key: &AffinePoint<C>,
+    prehash: &[u8; WIDTH_BYTES],
+    signature: &Signature<C>,
+) -> bool {
+    let e = FieldElement::<C::Scalar>::reduce(U256::from_be_bytes(prehash));
+    let w = signature.s.inv().unwrap();
+    let u1 = *(e * w).value();
+    let u2 = *(signature.r * w).value();
+
+    // Compute u1 * G + u2 * Q with a single chain of doublings.
+    let g = JacobianPoint::from(AffinePoint::<C>::generator());
+    let q = JacobianPoint::from(*public_key);
+    let g_q = g.add(&q);
+    let mut point = JacobianPoint::IDENTITY;
+    for i in (0..u1.bits().max(u2.bits())).rev() {
+        point = point.double();
+        match (u1.bit(i), u2.bit(i)) {
+            (true, true) => point = point.add(&g_q),
+            (true, false) => point = point.add(&g),
+            (false, true) => point = point.add(&q),
+            (false, false) => {}
+        }
+    }
+    match point.to_affine() {
+        Some(point) => FieldElement::<C::Scalar>::reduce(*point.x.value()) == signature.r,
+        None => false,
+    }
+}
+
+/// The base field of [Secp256k1].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Secp256k1Base;
+
+impl FieldParams for Secp256k1Base {
+    const MODULUS: U256 =
+        U256::from_be_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f");
+}
+
+/// The scalar field of [Secp256k1].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Secp256k1Scalar;
+
+impl FieldParams for Secp256k1Scalar {
+    const MODULUS: U256 =
+        U256::from_be_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+}
+
+/// The secp256k1 curve, as used by Bitcoin and Ethereum.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Secp256k1;
+
+impl Curve for Secp256k1 {
+    type Base = Secp256k1Base;
+    type Scalar = Secp256k1Scalar;
+
+    const A: FieldElement<Secp256k1Base> = FieldElement::ZERO;
+    const B: FieldElement<Secp256k1Base> = FieldElement::from_be_hex(
+        "0000000000000000000000000000000000000000000000000000000000000007",
+    );
+    const GENERATOR: (FieldElement<Secp256k1Base>, FieldElement<Secp256k1Base>) = (
+        FieldElement::from_be_hex(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        ),
+        FieldElement::from_be_hex(
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        ),
+    );
+}
+
+/// The base field of [P256].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct P256Base;
+
+impl FieldParams for P256Base {
+    const MODULUS: U256 =
+        U256::from_be_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+}
+
+/// The scalar field of [P256].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct P256Scalar;
+
+impl FieldParams for P256Scalar {
+    const MODULUS: U256 =
+        U256::from_be_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+}
+
+/// The NIST P-256 curve, also known as secp256r1.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct P256;
+
+impl Curve for P256 {
+    type Base = P256Base;
+    type Scalar = P256Scalar;
+
+    const A: FieldElement<P256Base> = FieldElement::from_be_hex(
+        "ffffffff00000001000000000000000000000000fffffffffffffffffffffffc",
+    );
+    const B: FieldElement<P256Base> = FieldElement::from_be_hex(
+        "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+    );
+    const GENERATOR: (FieldElement<P256Base>, FieldElement<P256Base>) = (
+        FieldElement::from_be_hex(
+            "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+        ),
+        FieldElement::from_be_hex(
+            "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::signature::Signer;
+
+    use super::*;
+
+    fn secp256k1_key(secret: u8) -> k256::ecdsa::SigningKey {
+        k256::ecdsa::SigningKey::from_bytes(&[secret; 32].into()).unwrap()
+    }
+
+    fn p256_key(secret: u8) -> p256::ecdsa::SigningKey {
+        p256::ecdsa::SigningKey::from_bytes(&[secret; 32].into()).unwrap()
+    }
+
+    #[test]
+    fn generators_on_curve() {
+        let (x, y) = Secp256k1::GENERATOR;
+        assert_eq!(
+            AffinePoint::<Secp256k1>::new(x, y),
+            Some(AffinePoint::generator())
+        );
+        let (x, y) = P256::GENERATOR;
+        assert_eq!(
+            AffinePoint::<P256>::new(x, y),
+            Some(AffinePoint::generator())
+        );
+        assert_eq!(AffinePoint::<P256>::new(y, x), None);
+    }
+
+    #[test]
+    fn scalar_mul() {
+        for secret in [1, 2, 7, 0x7f] {
+            let key = secp256k1_key(secret);
+            let expected = key.verifying_key().to_encoded_point(false);
+            let point = JacobianPoint::from(AffinePoint::<Secp256k1>::generator())
+                .mul(&U256::from_be_bytes(&[secret; 32]))
+                .to_affine()
+                .unwrap();
+            assert_eq!(point.to_uncompressed().as_slice(), expected.as_bytes());
+
+            let key = p256_key(secret);
+            let expected = key.verifying_key().to_encoded_point(false);
+            let point = JacobianPoint::from(AffinePoint::<P256>::generator())
+                .mul(&U256::from_be_bytes(&[secret; 32]))
+                .to_affine()
+                .unwrap();
+            assert_eq!(point.to_uncompressed().as_slice(), expected.as_bytes());
+        }
+
+        let order = Secp256k1Scalar::MODULUS;
+        let g = JacobianPoint::from(AffinePoint::<Secp256k1>::generator());
+        assert!(g.mul(&order).is_identity());
+    }
+
+    #[test]
+    fn sec1_decoding() {
+        for secret in [3, 0x42] {
+            let key = secp256k1_key(secret);
+            let compressed = key.verifying_key().to_encoded_point(true);
+            let uncompressed = key.verifying_key().to_encoded_point(false);
+            let point = AffinePoint::<Secp256k1>::from_sec1(compressed.as_bytes()).unwrap();
+            assert_eq!(point.to_uncompressed().as_slice(), uncompressed.as_bytes());
+            assert_eq!(
+                AffinePoint::<Secp256k1>::from_sec1(uncompressed.as_bytes()),
+                Some(point)
+            );
+        }
+        assert_eq!(AffinePoint::<Secp256k1>::from_sec1(&[4; 65]), None);
+        assert_eq!(AffinePoint::<Secp256k1>::from_sec1(&[]), None);
+    }
+
+    #[test]
+    fn ecdsa_secp256k1() {
+        let key = secp256k1_key(0x11);
+        let public_key = AffinePoint::<Secp256k1>::from_sec1(
+            key.verifying_key().to_encoded_point(false).as_bytes(),
+        )
+        .unwrap();
+        for message in [&b""[..], &b"hello"[..], &[0xab; 200][..]] {
+            let signature: k256::ecdsa::Signature = key.sign(message);
+            let signature = Signature::from_bytes(&signature.to_bytes()).unwrap();
+            assert!(verify_ecdsa(&public_key, message, &signature));
+            assert!(!verify_ecdsa(&public_key, b"other", &signature));
+            let wrong_key = AffinePoint::generator();
+            assert!(!verify_ecdsa(&wrong_key, message, &signature));
+        }
+    }
+
+    #[test]
+    fn ecdsa_p256() {
+        let key = p256_key(0x22);
+        let public_key =
+            AffinePoint::<P256>::from_sec1(key.verifying_key().to_encoded_point(true).as_bytes())
+                .unwrap();
+        for message in [&b""[..], &b"hello"[..], &[0xab; 200][..]] {
+            let signature: p256::ecdsa::Signature = key.sign(message);
+            let bytes = signature.to_bytes();
+            let signature = Signature::from_bytes(&bytes).unwrap();
+            assert!(verify_ecdsa(&public_key, message, &signature));
+            assert!(!verify_ecdsa(&public_key, b"other", &signature));
+
+            let mut tampered = bytes;
+            tampered[40] ^= 1;
+            let tampered = Signature::from_bytes(&tampered).unwrap();
+            assert!(!verify_ecdsa(&public_key, message, &tampered));
+        }
+        assert_eq!(Signature::<P256>::from_bytes(&[0; 64]), None);
+    }
+}
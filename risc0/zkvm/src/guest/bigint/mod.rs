@@ -0,0 +1,484 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 256-bit modular arithmetic on the BigInt accelerator.
+//!
+//! A [FieldElement] is an integer modulo the prime given by its
+//! [FieldParams]. Multiplication is a single call to the BigInt accelerator
+//! ([sys_bigint]) in the guest, which makes it about as cheap as an addition;
+//! inversion and square roots are computed by exponentiation. Outside the
+//! zkVM the same operations are implemented in software, so code using this
+//! module can be tested on the host.
+//!
+//! The [ec] module builds elliptic curve arithmetic and ECDSA signature
+//! verification for secp256k1 and P-256 on top of these field types.
+//!
+//! [sys_bigint]: risc0_zkvm_platform::syscall::sys_bigint
+
+pub mod ec;
+
+use core::{
+    cmp::Ordering,
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use risc0_zkvm_platform::syscall::bigint::{WIDTH_BYTES, WIDTH_WORDS};
+
+/// An unsigned 256-bit integer, in the little-endian word order used by the
+/// BigInt accelerator.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct U256(pub [u32; WIDTH_WORDS]);
+
+impl U256 {
+    /// The integer 0.
+    pub const ZERO: Self = Self([0; WIDTH_WORDS]);
+
+    /// The integer 1.
+    pub const ONE: Self = Self([1, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Parse a big-endian hex string of exactly 64 digits.
+    ///
+    /// Panics if the string is malformed, which is a compile time error when
+    /// used to define a constant.
+    pub const fn from_be_hex(hex: &str) -> Self {
+        let hex = hex.as_bytes();
+        assert!(hex.len() == 2 * WIDTH_BYTES, "Expected 64 hex digits");
+        let mut words = [0; WIDTH_WORDS];
+        let mut i = 0;
+        while i < hex.len() {
+            let digit = match hex[i] {
+                b'0'..=b'9' => hex[i] - b'0',
+                b'a'..=b'f' => hex[i] - b'a' + 10,
+                b'A'..=b'F' => hex[i] - b'A' + 10,
+                _ => panic!("Invalid hex digit"),
+            };
+            // Digit i counts from the most significant end.
+            let nibble = hex.len() - 1 - i;
+            words[nibble / 8] |= (digit as u32) << (4 * (nibble % 8));
+            i += 1;
+        }
+        Self(words)
+    }
+
+    /// Decode a big-endian byte string.
+    pub fn from_be_bytes(bytes: &[u8; WIDTH_BYTES]) -> Self {
+        let mut words = [0; WIDTH_WORDS];
+        for (word, chunk) in words.iter_mut().zip(bytes.rchunks_exact(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Self(words)
+    }
+
+    /// Encode as a big-endian byte string.
+    pub fn to_be_bytes(&self) -> [u8; WIDTH_BYTES] {
+        let mut bytes = [0; WIDTH_BYTES];
+        for (chunk, word) in bytes.rchunks_exact_mut(4).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Returns whether this is zero.
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    /// Returns bit `i`, counting from the least significant bit.
+    pub fn bit(&self, i: usize) -> bool {
+        (self.0[i / 32] >> (i % 32)) & 1 == 1
+    }
+
+    /// Returns the number of bits needed to represent this integer.
+    pub fn bits(&self) -> usize {
+        match self.0.iter().rposition(|word| *word != 0) {
+            Some(i) => 32 * i + 32 - self.0[i].leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Returns `self + rhs` modulo 2^256, and whether it overflowed.
+    pub fn overflowing_add(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0; WIDTH_WORDS];
+        let mut carry = false;
+        for ((out, lhs), rhs) in out.iter_mut().zip(self.0).zip(rhs.0) {
+            let (sum, c1) = lhs.overflowing_add(rhs);
+            let (sum, c2) = sum.overflowing_add(carry as u32);
+            *out = sum;
+            carry = c1 || c2;
+        }
+        (Self(out), carry)
+    }
+
+    /// Returns `self - rhs` modulo 2^256, and whether it underflowed.
+    pub fn overflowing_sub(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0; WIDTH_WORDS];
+        let mut borrow = false;
+        for ((out, lhs), rhs) in out.iter_mut().zip(self.0).zip(rhs.0) {
+            let (diff, b1) = lhs.overflowing_sub(rhs);
+            let (diff, b2) = diff.overflowing_sub(borrow as u32);
+            *out = diff;
+            borrow = b1 || b2;
+        }
+        (Self(out), borrow)
+    }
+
+    // Shifts left by one bit, shifting in `bit`, and returns the bit shifted
+    // out.
+    fn shl1(&self, bit: bool) -> (Self, bool) {
+        let mut out = [0; WIDTH_WORDS];
+        let mut carry = bit as u32;
+        for (out, word) in out.iter_mut().zip(self.0) {
+            *out = (word << 1) | carry;
+            carry = word >> 31;
+        }
+        (Self(out), carry == 1)
+    }
+
+    // Shifts right by `bits`, which must be less than 32.
+    fn shr(&self, bits: u32) -> Self {
+        let mut out = [0; WIDTH_WORDS];
+        for (i, out) in out.iter_mut().enumerate() {
+            let high = match self.0.get(i + 1) {
+                Some(word) if bits > 0 => word << (32 - bits),
+                _ => 0,
+            };
+            *out = (self.0[i] >> bits) | high;
+        }
+        Self(out)
+    }
+}
+
+// Compares in constant context, where `Ord` can't be used.
+const fn less_than(lhs: &U256, rhs: &U256) -> bool {
+    let mut i = WIDTH_WORDS;
+    while i > 0 {
+        i -= 1;
+        if lhs.0[i] != rhs.0[i] {
+            return lhs.0[i] < rhs.0[i];
+        }
+    }
+    false
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u32> for U256 {
+    fn from(value: u32) -> Self {
+        let mut words = [0; WIDTH_WORDS];
+        words[0] = value;
+        Self(words)
+    }
+}
+
+/// Returns `x * y mod modulus`, where at least one of `x` and `y` is less than
+/// `modulus`.
+fn mul_mod(x: &U256, y: &U256, modulus: &U256) -> U256 {
+    #[cfg(target_os = "zkvm")]
+    {
+        let mut result = [0; WIDTH_WORDS];
+        unsafe {
+            risc0_zkvm_platform::syscall::sys_bigint(
+                &mut result,
+                risc0_zkvm_platform::syscall::bigint::OP_MULTIPLY,
+                &x.0,
+                &y.0,
+                &modulus.0,
+            );
+        }
+        let result = U256(result);
+        // Every other operation relies on elements being fully reduced.
+        assert!(result < *modulus, "BigInt accelerator result not reduced");
+        result
+    }
+    #[cfg(not(target_os = "zkvm"))]
+    {
+        let mut product = [0u32; 2 * WIDTH_WORDS];
+        for (i, x) in x.0.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, y) in y.0.iter().enumerate() {
+                let acc = *x as u64 * *y as u64 + product[i + j] as u64 + carry;
+                product[i + j] = acc as u32;
+                carry = acc >> 32;
+            }
+            product[i + WIDTH_WORDS] = carry as u32;
+        }
+
+        // Bitwise long division, keeping only the remainder.
+        let mut rem = U256::ZERO;
+        for i in (0..product.len() * 32).rev() {
+            let (shifted, carry) = rem.shl1((product[i / 32] >> (i % 32)) & 1 == 1);
+            rem = shifted;
+            if carry || rem >= *modulus {
+                rem = rem.overflowing_sub(modulus).0;
+            }
+        }
+        rem
+    }
+}
+
+/// The parameters of a prime field.
+pub trait FieldParams: Copy + Debug + Eq + 'static {
+    /// The modulus of the field, which must be an odd prime.
+    const MODULUS: U256;
+}
+
+/// An element of the prime field given by `P`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FieldElement<P: FieldParams> {
+    value: U256,
+    phantom: PhantomData<P>,
+}
+
+impl<P: FieldParams> FieldElement<P> {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_canonical(U256::ZERO);
+
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::from_canonical(U256::ONE);
+
+    const fn from_canonical(value: U256) -> Self {
+        Self {
+            value,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Parse a big-endian hex string of exactly 64 digits.
+    ///
+    /// Panics if the string is malformed or not less than the modulus, which
+    /// is a compile time error when used to define a constant.
+    pub const fn from_be_hex(hex: &str) -> Self {
+        let value = U256::from_be_hex(hex);
+        assert!(
+            less_than(&value, &P::MODULUS),
+            "Value not less than modulus"
+        );
+        Self::from_canonical(value)
+    }
+
+    /// Returns the element with the given value, or `None` if it is not less
+    /// than the modulus.
+    pub fn new(value: U256) -> Option<Self> {
+        (value < P::MODULUS).then(|| Self::from_canonical(value))
+    }
+
+    /// Returns the element congruent to `value`.
+    pub fn reduce(value: U256) -> Self {
+        Self::from_canonical(mul_mod(&value, &U256::ONE, &P::MODULUS))
+    }
+
+    /// Decode a big-endian byte string, or return `None` if it is not less
+    /// than the modulus.
+    pub fn from_be_bytes(bytes: &[u8; WIDTH_BYTES]) -> Option<Self> {
+        Self::new(U256::from_be_bytes(bytes))
+    }
+
+    /// Encode as a big-endian byte string.
+    pub fn to_be_bytes(&self) -> [u8; WIDTH_BYTES] {
+        self.value.to_be_bytes()
+    }
+
+    /// Returns the value of this element, which is less than the modulus.
+    pub fn value(&self) -> &U256 {
+        &self.value
+    }
+
+    /// Returns whether this is zero.
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    // Returns twice this element.
+    fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// Returns the square of this element.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Returns this element raised to the power `exp`.
+    pub fn pow(&self, exp: &U256) -> Self {
+        let mut result = Self::ONE;
+        for i in (0..exp.bits()).rev() {
+            result = result.square();
+            if exp.bit(i) {
+                result = result * *self;
+            }
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse of this element, or `None` if it is
+    /// zero.
+    pub fn inv(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        // By Fermat's little theorem, x^(p - 2) * x = 1.
+        let exp = P::MODULUS.overflowing_sub(&U256::from(2)).0;
+        Some(self.pow(&exp))
+    }
+
+    /// Returns a square root of this element, or `None` if it has none.
+    ///
+    /// Panics unless the modulus is 3 mod 4, as it is for the base fields of
+    /// secp256k1 and P-256.
+    pub fn sqrt(&self) -> Option<Self> {
+        assert_eq!(P::MODULUS.0[0] % 4, 3, "Unsupported modulus");
+        // (p + 1) / 4, which doesn't overflow since p is odd.
+        let exp = P::MODULUS.shr(2).overflowing_add(&U256::ONE).0;
+        let root = self.pow(&exp);
+        (root.square() == *self).then_some(root)
+    }
+}
+
+impl<P: FieldParams> Add for FieldElement<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (sum, carry) = self.value.overflowing_add(&rhs.value);
+        if carry || sum >= P::MODULUS {
+            Self::from_canonical(sum.overflowing_sub(&P::MODULUS).0)
+        } else {
+            Self::from_canonical(sum)
+        }
+    }
+}
+
+impl<P: FieldParams> Sub for FieldElement<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, borrow) = self.value.overflowing_sub(&rhs.value);
+        if borrow {
+            Self::from_canonical(diff.overflowing_add(&P::MODULUS).0)
+        } else {
+            Self::from_canonical(diff)
+        }
+    }
+}
+
+impl<P: FieldParams> Mul for FieldElement<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_canonical(mul_mod(&self.value, &rhs.value, &P::MODULUS))
+    }
+}
+
+impl<P: FieldParams> Neg for FieldElement<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+#[cfg(all(test, feature = "prove"))]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::{ec::Secp256k1Base, *};
+    use crate::sha::{Impl, Sha256};
+
+    type Fe = FieldElement<Secp256k1Base>;
+
+    fn to_biguint(value: &U256) -> BigUint {
+        BigUint::from_slice(&value.0)
+    }
+
+    fn from_biguint(value: &BigUint) -> U256 {
+        let mut words = value.to_u32_digits();
+        words.resize(WIDTH_WORDS, 0);
+        U256(words.try_into().unwrap())
+    }
+
+    fn elements() -> impl Iterator<Item = Fe> {
+        let modulus = Secp256k1Base::MODULUS;
+        let edge = [
+            U256::ZERO,
+            U256::ONE,
+            U256::from(2),
+            modulus.overflowing_sub(&U256::ONE).0,
+        ];
+        let hashed = (0u32..8).map(|i| {
+            let digest = Impl::hash_words(&[i]);
+            U256::from_be_bytes(digest.as_bytes().try_into().unwrap())
+        });
+        edge.into_iter().chain(hashed).map(Fe::reduce)
+    }
+
+    #[test]
+    fn u256_encoding() {
+        let value =
+            U256::from_be_hex("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8 + 1);
+        assert_eq!(value, U256::from_be_bytes(&bytes));
+        assert_eq!(value.to_be_bytes(), bytes);
+        assert_eq!(value.0[0], 0x1d1e1f20);
+        assert_eq!(value.bits(), 249);
+        assert_eq!(to_biguint(&value), BigUint::from_bytes_be(&bytes));
+    }
+
+    #[test]
+    fn field_ops() {
+        let p = to_biguint(&Secp256k1Base::MODULUS);
+        for x in elements() {
+            let bx = to_biguint(x.value());
+            assert_eq!(x, Fe::reduce(*x.value()));
+            assert_eq!(*(-x).value(), from_biguint(&((&p - &bx) % &p)));
+            for y in elements() {
+                let by = to_biguint(y.value());
+                assert_eq!(*(x + y).value(), from_biguint(&((&bx + &by) % &p)));
+                assert_eq!(*(x - y).value(), from_biguint(&((&bx + &p - &by) % &p)));
+                assert_eq!(*(x * y).value(), from_biguint(&((&bx * &by) % &p)));
+                assert_eq!(*x.pow(y.value()).value(), from_biguint(&bx.modpow(&by, &p)));
+            }
+            match x.inv() {
+                Some(inv) => assert_eq!(x * inv, Fe::ONE),
+                None => assert!(x.is_zero()),
+            }
+            if let Some(root) = x.square().sqrt() {
+                assert!(root == x || root == -x);
+            } else {
+                panic!("{x:?} squared has no square root");
+            }
+        }
+    }
+
+    #[test]
+    fn reduce() {
+        let max = U256([u32::MAX; WIDTH_WORDS]);
+        let p = to_biguint(&Secp256k1Base::MODULUS);
+        assert_eq!(
+            *Fe::reduce(max).value(),
+            from_biguint(&(to_biguint(&max) % p))
+        );
+        assert_eq!(Fe::new(Secp256k1Base::MODULUS), None);
+    }
+}
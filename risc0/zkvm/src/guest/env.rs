@@ -14,23 +14,30 @@
 
 //! Functions for interacting with the host environment.
 
-use core::{cell::UnsafeCell, default::Default, mem::MaybeUninit, ptr, ptr::null_mut, slice};
+use core::{
+    cell::UnsafeCell, cmp::min, default::Default, mem::MaybeUninit, ops::Range, ptr, ptr::null_mut,
+    slice,
+};
 
 use bytemuck::Pod;
-use risc0_zkp::core::digest::{Digest, DIGEST_BYTES, DIGEST_WORDS};
+use risc0_zkp::{
+    core::digest::{Digest, DIGEST_BYTES, DIGEST_WORDS},
+    verify::VerificationError,
+};
 use risc0_zkvm_platform::{
     fileno, memory, syscall,
     syscall::{
-        nr::SYS_LOG, sys_alloc_words, sys_cycle_count, sys_halt, sys_log, sys_pause, sys_read,
-        sys_read_words, sys_write, syscall_0, syscall_2, SyscallName,
+        nr, nr::SYS_LOG, sys_alloc_words, sys_cycle_count, sys_halt, sys_input, sys_log, sys_pause,
+        sys_read, sys_read_words, sys_write, syscall_0, syscall_1, syscall_2, SyscallName,
     },
-    WORD_SIZE,
+    PAGE_SIZE, WORD_SIZE,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     align_up,
     guest::{memory_barrier, sha},
+    receipt::{ExitCode, SessionReceipt},
     serde::{Deserializer, Result as SerdeResult, Serializer, WordRead, WordWrite},
     sha::rust_crypto::{Digest as _, Output, Sha256},
 };
@@ -65,6 +72,17 @@ impl<T: Default> Once<T> {
 
 static mut HASHER: Option<Sha256> = None;
 
+const INPUT_PAGES: usize = memory::INPUT.len_bytes() / PAGE_SIZE;
+
+/// The length of the input blob, once it has been asked for.
+static mut INPUT_LEN: Option<usize> = None;
+
+/// The public input, once it has been received and bound.
+static mut PUBLIC_INPUT: Option<&'static [u8]> = None;
+
+/// One bit per page of [memory::INPUT], set once the page has been copied in.
+static mut INPUT_RESIDENT: [u32; INPUT_PAGES / 32] = [0; INPUT_PAGES / 32];
+
 pub(crate) fn init() {
     unsafe { HASHER = Some(Sha256::new()) };
 }
@@ -112,15 +130,155 @@ pub fn send_recv_slice<T: Pod, U: Pod>(syscall_name: SyscallName, to_host: &[T])
 }
 
 /// Read private data from the host and deserializes it.
+///
+/// Panics if the data cannot be deserialized as a `T`; use [try_read] to
+/// handle malformed input.
 pub fn read<T: DeserializeOwned>() -> T {
     stdin().read()
 }
 
+/// Read private data from the host and deserializes it, returning an error if
+/// it cannot be deserialized as a `T`.
+///
+/// A guest that returns the error from a fallible entrypoint commits it to the
+/// journal, so that rejecting malformed input can be proven rather than
+/// aborting the guest.
+pub fn try_read<T: DeserializeOwned>() -> SerdeResult<T> {
+    stdin().try_read()
+}
+
+/// Read private data written by the host in the self-describing encoding, such
+/// as with [to_vec_tagged](crate::serde::to_vec_tagged), and deserializes it.
+///
+/// Unlike [read], this supports types that need `deserialize_any`, such as
+/// those using `#[serde(untagged)]` or `#[serde(flatten)]`. Panics if the data
+/// cannot be deserialized as a `T`; use [try_read_tagged] to handle malformed
+/// input.
+pub fn read_tagged<T: DeserializeOwned>() -> T {
+    try_read_tagged().unwrap_or_else(|err| panic!("Failed to deserialize input: {err}"))
+}
+
+/// Read private data written by the host in the self-describing encoding and
+/// deserializes it, returning an error if it cannot be deserialized as a `T`.
+pub fn try_read_tagged<T: DeserializeOwned>() -> SerdeResult<T> {
+    let mut deserializer = Deserializer::new_tagged(stdin());
+    T::deserialize(&mut deserializer).map_err(|err| err.at(deserializer.position()))
+}
+
 /// Read a slice from the host.
+///
+/// Panics if the input ends before the slice is filled; use [try_read_slice]
+/// to handle short input.
 pub fn read_slice<T: Pod>(slice: &mut [T]) {
     stdin().read_slice(slice)
 }
 
+/// Read a slice from the host, returning an error if the input ends before
+/// the slice is filled.
+pub fn try_read_slice<T: Pod>(slice: &mut [T]) -> SerdeResult<()> {
+    stdin().try_read_slice(slice)
+}
+
+/// Read a [SessionReceipt] from the host, and verify that it attests to the
+/// execution of the guest with the given `image_id`. Returns the receipt along
+/// with the [ExitCode] of its final segment.
+///
+/// The receipt is private input, so the host can choose which receipt to
+/// provide, but not forge one: a guest that commits `image_id` and the
+/// receipt's journal attests that some execution of that guest produced that
+/// journal. A receipt is valid whether that execution halted successfully or
+/// not, so callers must check the exit code, e.g. against
+/// `ExitCode::Halted(0)`, before trusting the journal.
+pub fn read_verified_receipt(
+    image_id: impl Into<Digest>,
+) -> Result<(SessionReceipt, ExitCode), VerificationError> {
+    let receipt: SessionReceipt = read();
+    receipt.verify(image_id)?;
+    let exit_code = receipt
+        .segments
+        .last()
+        .ok_or(VerificationError::ReceiptFormatError)?
+        .get_metadata()?
+        .exit_code;
+    Ok((receipt, exit_code))
+}
+
+/// Returns the length in bytes of the blob provided by the host with
+/// `ExecutorEnvBuilder::input_region`, or zero if there is none.
+pub fn input_region_len() -> usize {
+    unsafe {
+        *INPUT_LEN.get_or_insert_with(|| {
+            let syscall::Return(len, _) = syscall_1(nr::SYS_INPUT, null_mut(), 0, 0);
+            len as usize
+        })
+    }
+}
+
+/// Copies the whole blob provided by the host into the [memory::INPUT]
+/// region, and returns it.
+///
+/// Unlike [read] and [read_slice], the blob is written by the host into
+/// memory reserved for it, so the guest does not allocate heap memory for it.
+/// Like any syscall, writing the blob into guest memory costs cycles in
+/// proportion to its size.
+pub fn input_region() -> &'static [u8] {
+    input_region_range(0..input_region_len())
+}
+
+/// Copies `range` of the blob provided by the host into the [memory::INPUT]
+/// region, and returns it.
+///
+/// The blob is copied in a page at a time, and only the pages covering
+/// `range` that have not already been copied are requested from the host, so
+/// large blobs can be processed a piece at a time and each page is paid for
+/// once. Pages are never written again, so slices returned by earlier calls
+/// stay valid.
+pub fn input_region_range(range: Range<usize>) -> &'static [u8] {
+    let len = input_region_len();
+    assert!(range.start <= range.end && range.end <= len);
+    for page in range.start / PAGE_SIZE..align_up(range.end, PAGE_SIZE) / PAGE_SIZE {
+        let (idx, bit) = (page / 32, 1 << (page % 32));
+        unsafe {
+            if INPUT_RESIDENT[idx] & bit != 0 {
+                continue;
+            }
+            let offset = page * PAGE_SIZE;
+            let nwords = align_up(min(PAGE_SIZE, len - offset), WORD_SIZE) / WORD_SIZE;
+            let base = (memory::INPUT.start() + offset) as *mut u32;
+            syscall_1(nr::SYS_INPUT, base, nwords, offset as u32);
+            INPUT_RESIDENT[idx] |= bit;
+        }
+    }
+    unsafe {
+        slice::from_raw_parts(
+            (memory::INPUT.start() + range.start) as *const u8,
+            range.len(),
+        )
+    }
+}
+
+/// Returns the public input provided by the host.
+///
+/// The SHA-256 of the public input is checked against the digest recorded in
+/// the receipt, so a verifier who is given the input along with the receipt
+/// knows that the guest was run on it, without it having to be echoed into
+/// the journal. The input is only received and hashed on the first call.
+pub fn public_input() -> &'static [u8] {
+    unsafe {
+        PUBLIC_INPUT.get_or_insert_with(|| {
+            let input: &[u8] = send_recv_slice(nr::SYS_PUBLIC_INPUT, &[] as &[u8]);
+            let digest = if input.is_empty() {
+                Digest::default()
+            } else {
+                *<sha::Impl as crate::sha::Sha256>::hash_bytes(input)
+            };
+            let words: &[u32; DIGEST_WORDS] = digest.as_ref();
+            sys_input(words);
+            input
+        })
+    }
+}
+
 /// Serialize the given data and write it to the STDOUT of the zkVM.
 ///
 /// This is available to the host as the private output on the prover.
@@ -202,21 +360,49 @@ pub fn pause() {
     };
 }
 
+/// Halt the execution of the zkvm with the given user exit code.
+///
+/// The journal committed so far is included in the receipt along with
+/// [ExitCode::Halted](crate::receipt::ExitCode::Halted)`(code)`, so a guest
+/// can prove that it stopped early, for instance because its input was
+/// invalid.
+pub fn exit(code: u8) -> ! {
+    finalize(true, code);
+    unreachable!()
+}
+
 /// Reads and deserializes objects
 pub trait Read {
     /// Read data from the host.
-    fn read<T: DeserializeOwned>(&mut self) -> T;
+    ///
+    /// Panics if the data is malformed.
+    fn read<T: DeserializeOwned>(&mut self) -> T {
+        self.try_read()
+            .unwrap_or_else(|err| panic!("Failed to deserialize input: {err}"))
+    }
+
+    /// Read data from the host, returning an error if it is malformed.
+    fn try_read<T: DeserializeOwned>(&mut self) -> SerdeResult<T>;
 
     /// Read raw data from the host.
-    fn read_slice<T: Pod>(&mut self, buf: &mut [T]);
+    ///
+    /// Panics if the input ends before `buf` is filled.
+    fn read_slice<T: Pod>(&mut self, buf: &mut [T]) {
+        self.try_read_slice(buf)
+            .unwrap_or_else(|err| panic!("Failed to read input: {err}"))
+    }
+
+    /// Read raw data from the host, returning an error if the input ends
+    /// before `buf` is filled.
+    fn try_read_slice<T: Pod>(&mut self, buf: &mut [T]) -> SerdeResult<()>;
 }
 
 impl<R: Read + ?Sized> Read for &mut R {
-    fn read<T: DeserializeOwned>(&mut self) -> T {
-        (**self).read()
+    fn try_read<T: DeserializeOwned>(&mut self) -> SerdeResult<T> {
+        (**self).try_read()
     }
-    fn read_slice<T: Pod>(&mut self, buf: &mut [T]) {
-        (**self).read_slice(buf)
+    fn try_read_slice<T: Pod>(&mut self, buf: &mut [T]) -> SerdeResult<()> {
+        (**self).try_read_slice(buf)
     }
 }
 
@@ -254,16 +440,21 @@ impl FdReader {
 }
 
 impl Read for FdReader {
-    fn read<T: DeserializeOwned>(&mut self) -> T {
-        T::deserialize(&mut Deserializer::new(self)).unwrap()
+    fn try_read<T: DeserializeOwned>(&mut self) -> SerdeResult<T> {
+        let mut deserializer = Deserializer::new(self);
+        T::deserialize(&mut deserializer).map_err(|err| err.at(deserializer.position()))
     }
 
-    fn read_slice<T: Pod>(&mut self, buf: &mut [T]) {
+    fn try_read_slice<T: Pod>(&mut self, buf: &mut [T]) -> SerdeResult<()> {
         if let Ok(words) = bytemuck::try_cast_slice_mut(buf) {
             // Reading words performs significantly better if we're word aligned.
-            self.read_words(words);
+            self.read_words(words)
         } else {
-            self.read_bytes_all(bytemuck::cast_slice_mut(buf));
+            let bytes = bytemuck::cast_slice_mut(buf);
+            if self.read_bytes_all(bytes) != bytes.len() {
+                return Err(crate::serde::Error::DeserializeUnexpectedEnd);
+            }
+            Ok(())
         }
     }
 }
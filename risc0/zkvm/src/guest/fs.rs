@@ -0,0 +1,190 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A read-only filesystem provided by the host.
+//!
+//! The host adds files with `ExecutorEnvBuilder::file`, or maps them to files
+//! on the host with `ExecutorEnvBuilder::host_file`, and the guest reads them
+//! by path:
+//!
+//! ```ignore
+//! use risc0_zkvm::guest::fs;
+//!
+//! let config = fs::read_to_string("/etc/app.toml").unwrap();
+//! ```
+//!
+//! With the `std` feature, [File] implements [std::io::Read], so it can be
+//! passed to libraries that read their input from a reader.
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{Display, Formatter},
+    ptr::null_mut,
+};
+
+use risc0_zkvm_platform::syscall::{self, nr, sys_read, syscall_1, syscall_2};
+
+/// Returned by the host when a path does not exist.
+const NOT_FOUND: u32 = u32::MAX;
+
+/// Errors returned when accessing the filesystem.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The path does not exist.
+    NotFound,
+
+    /// The file is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("No such file"),
+            Self::InvalidUtf8 => f.write_str("File is not valid UTF-8"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::NotFound => std::io::ErrorKind::NotFound,
+            Error::InvalidUtf8 => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// Metadata about a file.
+#[derive(Clone, Copy, Debug)]
+pub struct Metadata {
+    len: usize,
+}
+
+impl Metadata {
+    /// Returns the size of the file in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A file opened for reading.
+///
+/// The file is closed when it is dropped.
+pub struct File {
+    fd: u32,
+    len: usize,
+}
+
+impl File {
+    /// Opens the file at `path`.
+    pub fn open(path: &str) -> Result<File, Error> {
+        let syscall::Return(fd, len) = unsafe {
+            syscall_2(
+                nr::SYS_OPEN,
+                null_mut(),
+                0,
+                path.as_ptr() as u32,
+                path.len() as u32,
+            )
+        };
+        if fd == NOT_FOUND {
+            return Err(Error::NotFound);
+        }
+        Ok(File {
+            fd,
+            len: len as usize,
+        })
+    }
+
+    /// Returns the metadata of this file.
+    pub fn metadata(&self) -> Metadata {
+        Metadata { len: self.len }
+    }
+
+    /// Reads up to `buf.len()` bytes into `buf`, returning how many bytes were
+    /// read, which is zero at the end of the file.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        unsafe { sys_read(self.fd, buf.as_mut_ptr(), buf.len()) }
+    }
+
+    /// Reads the rest of the file and appends it to `buf`, returning how many
+    /// bytes were read.
+    pub fn read_to_end(&mut self, buf: &mut Vec<u8>) -> usize {
+        let start = buf.len();
+        buf.resize(start + self.len, 0);
+        let mut nread = 0;
+        loop {
+            let n = self.read(&mut buf[start + nread..]);
+            if n == 0 {
+                break;
+            }
+            nread += n;
+        }
+        buf.truncate(start + nread);
+        nread
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe { syscall_1(nr::SYS_CLOSE, null_mut(), 0, self.fd) };
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(File::read(self, buf))
+    }
+}
+
+/// Returns the metadata of the file at `path`, without opening it.
+pub fn metadata(path: &str) -> Result<Metadata, Error> {
+    let syscall::Return(status, len) = unsafe {
+        syscall_2(
+            nr::SYS_STAT,
+            null_mut(),
+            0,
+            path.as_ptr() as u32,
+            path.len() as u32,
+        )
+    };
+    if status == NOT_FOUND {
+        return Err(Error::NotFound);
+    }
+    Ok(Metadata { len: len as usize })
+}
+
+/// Reads the whole file at `path`.
+pub fn read(path: &str) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf);
+    Ok(buf)
+}
+
+/// Reads the whole file at `path` as a string.
+pub fn read_to_string(path: &str) -> Result<String, Error> {
+    String::from_utf8(read(path)?).map_err(|_| Error::InvalidUtf8)
+}
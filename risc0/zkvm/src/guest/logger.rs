@@ -0,0 +1,97 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [log] facade implementation that sends log records to the host.
+//!
+//! Once [init] has been called, messages logged by the guest and by the crates
+//! it uses through `log::info!` and friends are passed to the host's `log`
+//! facade with the `guest` target, along with the cycle they were logged at and
+//! their source location:
+//!
+//! ```ignore
+//! risc0_zkvm::entry!(main);
+//!
+//! pub fn main() {
+//!     risc0_zkvm::guest::logger::init();
+//!     log::info!("Starting up");
+//! }
+//! ```
+//!
+//! The host chooses the most verbose level the guest sends records at with
+//! `ExecutorEnvBuilder::log_level`. Formatting a record and sending it to the
+//! host costs cycles even if the host then discards it, so levels can also be
+//! disabled at compile time with the `max_level_*` and `release_max_level_*`
+//! features of the `log` crate.
+
+use core::ptr::null_mut;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use risc0_zkvm_platform::syscall::{nr, syscall_0, syscall_2, Return};
+
+use crate::serde::to_vec;
+
+struct Logger;
+
+static LOGGER: Logger = Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let msg = alloc::format!("{}", record.args());
+        let words = to_vec(&(
+            record.level() as u32,
+            record.module_path(),
+            record.file(),
+            record.line(),
+            msg.as_str(),
+        ))
+        .unwrap();
+        unsafe {
+            syscall_2(
+                nr::SYS_LOG_RECORD,
+                null_mut(),
+                0,
+                words.as_ptr() as u32,
+                words.len() as u32,
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the guest logger, at the level requested by the host.
+///
+/// Panics if another logger has already been installed.
+pub fn init() {
+    let Return(level, _) = unsafe { syscall_0(nr::SYS_LOG_LEVEL, null_mut(), 0) };
+    let level = match level {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    // SAFETY: the guest is single threaded, so nothing can be logging while the
+    // logger is installed.
+    unsafe { log::set_logger_racy(&LOGGER) }.expect("A logger is already installed");
+    log::set_max_level(level);
+}
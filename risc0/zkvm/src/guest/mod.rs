@@ -47,6 +47,21 @@
 //! entrypoint, [env::read] is used to load the two factors, and [env::commit]
 //! is used to make their composite product publically available.
 //!
+//! Guests which log through the `log` crate, directly or through the crates
+//! they use, can send their log records to the host by calling
+//! [logger::init].
+//!
+//! The [bigint] module provides 256-bit modular arithmetic on the BigInt
+//! accelerator, along with secp256k1 and P-256 ECDSA signature verification
+//! that is much cheaper than a pure software implementation.
+//!
+//! By default, the guest heap never frees memory, which makes allocation very
+//! cheap but means that a guest which keeps allocating temporary buffers can
+//! run out of memory even when it holds on to little at any one time. Such
+//! guests can enable the `heap-reclaim` feature of `risc0-zkvm` to use an
+//! allocator that reuses freed memory, at the cost of a few more cycles per
+//! allocation.
+//!
 //! If you encounter problems building zkVM guest code, you can see if we have a
 //! known workaround for your issue by looking in our
 //! [rust guest workarounds](https://github.com/risc0/risc0/issues?q=is%3Aissue+is%3Aopen+label%3A%22rust+guest+workarounds%22)
@@ -58,7 +73,10 @@
 #![deny(missing_docs)]
 
 mod alloc;
+pub mod bigint;
 pub mod env;
+pub mod fs;
+pub mod logger;
 pub mod sha;
 
 use core::{arch::asm, mem, ptr};
@@ -137,6 +155,11 @@ mod handlers {
 
 /// Used for defining a main entrypoint.
 ///
+/// The entrypoint either returns `()`, or a `Result<(), E>` where `E` can be
+/// serialized. An error is committed to the journal and the guest halts with
+/// exit code 1, so that the rejection of an input can be proven just like a
+/// successful run. See [Termination].
+///
 /// # Example
 ///
 /// ```ignore
@@ -148,14 +171,37 @@ mod handlers {
 macro_rules! entry {
     ($path:path) => {
         #[no_mangle]
-        fn __main() {
-            // type check the given path
-            let f: fn() = $path;
-            f()
+        fn __main() -> u8 {
+            $crate::guest::Termination::report($path())
         }
     };
 }
 
+/// The return type of a guest entrypoint, which is turned into the user exit
+/// code the guest halts with.
+pub trait Termination {
+    /// Returns the user exit code for this value.
+    fn report(self) -> u8;
+}
+
+impl Termination for () {
+    fn report(self) -> u8 {
+        0
+    }
+}
+
+impl<E: serde::Serialize> Termination for Result<(), E> {
+    fn report(self) -> u8 {
+        match self {
+            Ok(()) => 0,
+            Err(err) => {
+                env::commit(&err);
+                1
+            }
+        }
+    }
+}
+
 #[cfg(target_os = "zkvm")]
 #[no_mangle]
 unsafe extern "C" fn __start() {
@@ -173,11 +219,11 @@ unsafe extern "C" fn __start() {
     env::init();
 
     extern "Rust" {
-        fn __main();
+        fn __main() -> u8;
     }
-    __main();
+    let exit_code = __main();
 
-    env::finalize(true, 0);
+    env::finalize(true, exit_code);
 }
 
 #[cfg(target_os = "zkvm")]
@@ -26,7 +26,7 @@ use bytemuck::Pod;
 use risc0_zkvm_platform::{
     fileno,
     syscall::{
-        nr::{SYS_GETENV, SYS_READ, SYS_READ_AVAIL, SYS_WRITE},
+        nr::{SYS_GETENV, SYS_PUBLIC_INPUT, SYS_READ, SYS_READ_AVAIL, SYS_WRITE},
         SyscallName,
     },
 };
@@ -58,6 +58,7 @@ pub struct ExecutorEnv<'a> {
     syscalls: SyscallTable<'a>,
     pub(crate) io: Rc<RefCell<PosixIo<'a>>>,
     input: Vec<u8>,
+    pub(crate) public_input: Vec<u8>,
     pub(crate) trace_callback: Option<Rc<RefCell<dyn FnMut(TraceEvent) -> Result<()> + 'a>>>,
 }
 
@@ -96,6 +97,7 @@ impl<'a> Default for ExecutorEnvBuilder<'a> {
                 syscalls: Default::default(),
                 io: Default::default(),
                 input: Default::default(),
+                public_input: Default::default(),
                 trace_callback: Default::default(),
             },
         }
@@ -107,6 +109,7 @@ impl<'a> ExecutorEnvBuilder<'a> {
     pub fn build(&mut self) -> ExecutorEnv<'a> {
         let mut result = self.clone();
         let getenv = syscalls::Getenv(self.inner.env_vars.clone());
+        let public_input = syscalls::PublicInput(self.inner.public_input.clone());
         if !self.inner.input.is_empty() {
             let reader = Cursor::new(self.inner.input.clone());
             result
@@ -118,6 +121,7 @@ impl<'a> ExecutorEnvBuilder<'a> {
         let io = result.inner.io.clone();
         result
             .syscall(SYS_GETENV, getenv)
+            .syscall(SYS_PUBLIC_INPUT, public_input)
             .syscall(SYS_READ, io.clone())
             .syscall(SYS_READ_AVAIL, io.clone())
             .syscall(SYS_WRITE, io);
@@ -158,6 +162,16 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add public input, which the guest reads with
+    /// `risc0_zkvm::guest::env::public_input`. Its SHA-256 is bound into the
+    /// segments of the resulting [Session](crate::Session).
+    pub fn public_input<T: Pod>(&mut self, slice: &[T]) -> &mut Self {
+        self.inner
+            .public_input
+            .extend_from_slice(bytemuck::cast_slice(slice));
+        self
+    }
+
     /// Add a handler for a raw syscall implementation.
     pub fn syscall(&mut self, syscall: SyscallName, handler: impl Syscall + 'a) -> &mut Self {
         self.inner.syscalls.with_syscall(syscall, handler);
@@ -428,6 +428,23 @@ pub(crate) mod syscalls {
         }
     }
 
+    /// Sends the public input to the guest, which gets back its length in
+    /// bytes.
+    pub(crate) struct PublicInput(pub Vec<u8>);
+    impl Syscall for PublicInput {
+        fn syscall(
+            &mut self,
+            _syscall: &str,
+            _ctx: &mut dyn SyscallContext,
+            to_guest: &mut [u32],
+        ) -> Result<(u32, u32)> {
+            let nbytes = min(self.0.len(), to_guest.len() * WORD_SIZE);
+            let to_guest_u8s: &mut [u8] = bytemuck::cast_slice_mut(to_guest);
+            to_guest_u8s[..nbytes].clone_from_slice(&self.0[..nbytes]);
+            Ok((self.0.len() as u32, 0))
+        }
+    }
+
     pub(crate) struct Log;
     impl Syscall for Log {
         fn syscall(
@@ -47,7 +47,7 @@ use num_bigint::BigUint;
 use num_traits::Zero;
 use risc0_zkp::{
     core::{
-        digest::{DIGEST_BYTES, DIGEST_WORDS},
+        digest::{Digest, DIGEST_BYTES, DIGEST_WORDS},
         hash::sha::{BLOCK_BYTES, BLOCK_WORDS},
         log2_ceil,
     },
@@ -72,6 +72,8 @@ pub use self::env::{ExecutorEnv, ExecutorEnvBuilder};
 use self::monitor::MemoryMonitor;
 use crate::receipt::ExitCode;
 #[cfg(all(feature = "template", feature = "ioexec"))]
+use crate::receipt::public_input_digest;
+#[cfg(all(feature = "template", feature = "ioexec"))]
 use crate::{
     align_up,
     opcode::{MajorType, OpCode},
@@ -100,6 +102,7 @@ pub struct Executor<'a> {
     segments: Vec<Box<dyn SegmentRef>>,
     insn_counter: u32,
     split_insn: Option<u32>,
+    input_digest: Digest,
 }
 
 /// This should replace the original exec struct.
@@ -167,6 +170,7 @@ impl<'a> Executor<'a> {
         let loader = Loader::new();
         let init_cycles = loader.init_cycles();
         let fini_cycles = loader.fini_cycles();
+        let input_digest = public_input_digest(&env.public_input);
 
         Self {
             env,
@@ -180,6 +184,7 @@ impl<'a> Executor<'a> {
             segments: Vec::new(),
             insn_counter: 0,
             split_insn: None,
+            input_digest,
         }
     }
 
@@ -227,6 +232,7 @@ impl<'a> Executor<'a> {
                         faults,
                         syscalls,
                         exit_code,
+                        self.input_digest,
                         self.split_insn,
                         log2_ceil(total_cycles.next_power_of_two()),
                         self.segments
@@ -255,10 +261,11 @@ impl<'a> Executor<'a> {
         };
 
         let exit_code = run_loop()?;
-        Ok(Session::new(
+        Ok(Session::new_with_input(
             take(&mut self.segments),
             journal.buf.take(),
             exit_code,
+            self.env.public_input.clone(),
         ))
     }
 
@@ -436,8 +443,12 @@ impl<'a> Executor<'a> {
     fn ecall_input(&mut self) -> Result<OpCodeResult> {
         log::debug!("ecall(input)");
         let in_addr = self.monitor.load_register(REG_A0);
-        self.monitor
+        let digest = self
+            .monitor
             .load_array::<{ DIGEST_WORDS * WORD_SIZE }>(in_addr);
+        if digest.as_slice() != self.input_digest.as_bytes() {
+            bail!("Guest public input digest does not match the public input");
+        }
         Ok(OpCodeResult::new(self.pc + WORD_SIZE as u32, None, 0, None))
     }
 
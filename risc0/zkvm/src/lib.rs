@@ -32,12 +32,15 @@ mod exec;
 pub mod ioexec;
 
 pub mod guest;
+pub mod merkle;
+mod method;
 #[cfg(any(feature = "prove", feature="template"))]
 mod opcode;
 #[cfg(feature = "prove")]
 pub mod prove;
 
 pub mod receipt;
+pub mod rpc;
 
 pub mod serde;
 #[cfg(feature = "prove")]
@@ -60,6 +63,7 @@ pub use self::binfmt::{elf::Program, image::MemoryImage};
 #[cfg(feature = "profiler")]
 pub use self::exec::profiler::Profiler;
 
+pub use self::method::GuestMethod;
 pub use self::receipt::{ExitCode, SegmentReceipt, SessionReceipt};
 #[cfg(feature = "prove")]
 pub use self::{
@@ -0,0 +1,331 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SHA-256 Merkle trees with inclusion proofs.
+//!
+//! This module works identically on the host and in the guest. Internal nodes
+//! are hashed with [Sha256::hash_pair], which in the guest is a single call to
+//! the SHA-256 accelerator, so verifying a proof costs one compression per
+//! level of the tree.
+//!
+//! Leaves are [Digest]s, which should be the SHA-256 of the leaf data, e.g.
+//! from [hash_leaf]. Since `hash_pair` applies the compression function without
+//! SHA-256 padding, a leaf digest can't be confused with an internal node.
+//! Trees are padded up to a power of two leaves with zero digests.
+//!
+//! The host typically builds a tree over a large dataset and commits to its
+//! root, and the guest then verifies lookups into the dataset:
+//!
+//! ```rust
+//! use risc0_zkvm::merkle::{hash_leaf, MerkleTree};
+//!
+//! let leaves: Vec<_> = ["a", "b", "c"].iter().map(|data| hash_leaf(data)).collect();
+//! let tree = MerkleTree::new(leaves);
+//!
+//! let proof = tree.prove(1);
+//! assert!(proof.verify(&tree.root(), &hash_leaf("b")));
+//!
+//! let proof = tree.prove_multi(&[0, 2]);
+//! assert!(proof.verify(&tree.root(), &[(0, hash_leaf("a")), (2, hash_leaf("c"))]));
+//! ```
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sha::{Digest, Impl, Sha256};
+
+/// Returns the leaf digest of `data`, which is its SHA-256.
+pub fn hash_leaf(data: impl AsRef<[u8]>) -> Digest {
+    *Impl::hash_bytes(data.as_ref())
+}
+
+fn hash_node(left: &Digest, right: &Digest) -> Digest {
+    *Impl::hash_pair(left, right)
+}
+
+/// A Merkle tree over a list of leaf digests.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    // The nodes of each level of the tree, from the padded leaves up to the
+    // root.
+    levels: Vec<Vec<Digest>>,
+    len: usize,
+}
+
+impl MerkleTree {
+    /// Build a tree over the given leaves.
+    pub fn new(leaves: Vec<Digest>) -> Self {
+        let len = leaves.len();
+        let mut level = leaves;
+        level.resize(len.next_power_of_two(), Digest::default());
+        let mut levels = vec![level];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks_exact(2)
+                .map(|pair| hash_node(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        Self { levels, len }
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> Digest {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Returns the number of levels below the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the number of leaves, not counting padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the leaves of the tree, not counting padding.
+    pub fn leaves(&self) -> &[Digest] {
+        &self.levels[0][..self.len]
+    }
+
+    /// Prove the inclusion of the leaf at `index`.
+    ///
+    /// Panics if `index` is out of range.
+    pub fn prove(&self, index: usize) -> MerkleProof {
+        assert!(index < self.len, "Leaf index {index} out of range");
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        MerkleProof {
+            index: index as u32,
+            siblings,
+        }
+    }
+
+    /// Prove the inclusion of the leaves at `indices` together, which is
+    /// smaller than separate proofs when they share parts of their paths.
+    ///
+    /// Panics if any index is out of range.
+    pub fn prove_multi(&self, indices: &[usize]) -> MultiProof {
+        let mut known: Vec<usize> = indices
+            .iter()
+            .map(|&index| {
+                assert!(index < self.len, "Leaf index {index} out of range");
+                index
+            })
+            .collect();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut nodes = Vec::new();
+        for level in self.levels[..self.depth()].iter() {
+            let mut next = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let index = known[i];
+                if index % 2 == 0 && known.get(i + 1) == Some(&(index + 1)) {
+                    i += 1;
+                } else {
+                    nodes.push(level[index ^ 1]);
+                }
+                next.push(index / 2);
+                i += 1;
+            }
+            known = next;
+        }
+        MultiProof {
+            depth: self.depth() as u32,
+            nodes,
+        }
+    }
+}
+
+/// A proof of the inclusion of a single leaf in a [MerkleTree].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MerkleProof {
+    index: u32,
+    siblings: Vec<Digest>,
+}
+
+impl MerkleProof {
+    /// Returns the index of the proven leaf.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// Returns the root of the tree in which `leaf` is at [Self::index].
+    pub fn root(&self, leaf: &Digest) -> Digest {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(*leaf, |node, (height, sibling)| {
+                if self.index.checked_shr(height as u32).unwrap_or(0) & 1 == 0 {
+                    hash_node(&node, sibling)
+                } else {
+                    hash_node(sibling, &node)
+                }
+            })
+    }
+
+    /// Verify that `leaf` is at [Self::index] in the tree with the given
+    /// `root`.
+    pub fn verify(&self, root: &Digest, leaf: &Digest) -> bool {
+        let in_range = self.index.checked_shr(self.siblings.len() as u32) == Some(0)
+            || self.siblings.len() >= u32::BITS as usize;
+        in_range && self.root(leaf) == *root
+    }
+}
+
+/// A proof of the inclusion of several leaves in a [MerkleTree], which
+/// contains each node needed to compute the root only once.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MultiProof {
+    depth: u32,
+    nodes: Vec<Digest>,
+}
+
+impl MultiProof {
+    /// Returns the root of the tree in which each leaf is at its index, or
+    /// `None` if the leaves don't match the ones the proof was generated for,
+    /// or give different digests for the same index.
+    pub fn root(&self, leaves: &[(usize, Digest)]) -> Option<Digest> {
+        let mut known = leaves.to_vec();
+        known.sort_unstable_by_key(|(index, _)| *index);
+        if known
+            .windows(2)
+            .any(|pair| pair[0].0 == pair[1].0 && pair[0].1 != pair[1].1)
+        {
+            return None;
+        }
+        known.dedup_by_key(|(index, _)| *index);
+        let (last, _) = known.last()?;
+        if last.checked_shr(self.depth).unwrap_or(0) != 0 {
+            return None;
+        }
+
+        let mut nodes = self.nodes.iter();
+        for _ in 0..self.depth {
+            let mut next = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (index, node) = known[i];
+                let parent = if index % 2 == 0 {
+                    match known.get(i + 1) {
+                        Some((sibling, right)) if *sibling == index + 1 => {
+                            i += 1;
+                            hash_node(&node, right)
+                        }
+                        _ => hash_node(&node, nodes.next()?),
+                    }
+                } else {
+                    hash_node(nodes.next()?, &node)
+                };
+                next.push((index / 2, parent));
+                i += 1;
+            }
+            known = next;
+        }
+        if nodes.next().is_some() {
+            return None;
+        }
+        Some(known[0].1)
+    }
+
+    /// Verify that each leaf is at its index in the tree with the given
+    /// `root`.
+    pub fn verify(&self, root: &Digest, leaves: &[(usize, Digest)]) -> bool {
+        self.root(leaves) == Some(*root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(len: usize) -> MerkleTree {
+        MerkleTree::new((0..len).map(|i| hash_leaf(i.to_le_bytes())).collect())
+    }
+
+    #[test]
+    fn root() {
+        let leaves: Vec<_> = (0..3u32).map(|i| hash_leaf(i.to_le_bytes())).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let expected = hash_node(
+            &hash_node(&leaves[0], &leaves[1]),
+            &hash_node(&leaves[2], &Digest::default()),
+        );
+        assert_eq!(tree.root(), expected);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.leaves(), leaves);
+        assert_eq!(MerkleTree::new(vec![leaves[0]]).root(), leaves[0]);
+    }
+
+    #[test]
+    fn single_proofs() {
+        for len in [1, 2, 5, 8, 13] {
+            let tree = tree(len);
+            for index in 0..len {
+                let proof = tree.prove(index);
+                assert_eq!(proof.index(), index);
+                assert!(proof.verify(&tree.root(), &tree.leaves()[index]));
+                let other = tree.leaves()[(index + 1) % len];
+                assert_eq!(proof.verify(&tree.root(), &other), len == 1);
+            }
+        }
+    }
+
+    #[test]
+    fn multi_proofs() {
+        let tree = tree(13);
+        let leaf = |index: usize| (index, tree.leaves()[index]);
+        for indices in [vec![0], vec![3, 2], vec![0, 1, 12], vec![5, 5, 6, 9]] {
+            let proof = tree.prove_multi(&indices);
+            let leaves: Vec<_> = indices.iter().map(|&index| leaf(index)).collect();
+            assert!(proof.verify(&tree.root(), &leaves));
+        }
+
+        let proof = tree.prove_multi(&[2, 3, 7]);
+        assert_eq!(proof.nodes.len(), 4);
+        assert!(!proof.verify(&tree.root(), &[leaf(2), leaf(3)]));
+        assert!(!proof.verify(&tree.root(), &[leaf(2), leaf(3), leaf(6)]));
+        assert!(!proof.verify(&tree.root(), &[leaf(2), leaf(3), (7, tree.leaves()[6])]));
+        assert!(!proof.verify(&tree.root(), &[]));
+
+        // A duplicate index must carry the same digest, whichever comes first.
+        let proof = tree.prove_multi(&[5, 5, 6]);
+        assert!(proof.verify(&tree.root(), &[leaf(5), leaf(5), leaf(6)]));
+        let other = (5, tree.leaves()[4]);
+        assert!(!proof.verify(&tree.root(), &[leaf(5), other, leaf(6)]));
+        assert!(!proof.verify(&tree.root(), &[other, leaf(5), leaf(6)]));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let tree = tree(6);
+        let proof = tree.prove(4);
+        let words = crate::serde::to_vec(&proof).unwrap();
+        assert_eq!(proof, crate::serde::from_slice(&words).unwrap());
+    }
+}
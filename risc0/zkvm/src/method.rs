@@ -0,0 +1,49 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Descriptors for the guest methods embedded by `risc0-build`.
+
+use crate::sha::Digest;
+
+/// A guest method embedded by `risc0-build`.
+///
+/// The generated `methods.rs` defines one [GuestMethod] constant per method,
+/// along with a `METHODS` slice holding all of them and a `method` function
+/// that looks one up by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestMethod {
+    /// The name of the method, which is the name of its binary target.
+    pub name: &'static str,
+
+    /// The contents of the method's ELF binary.
+    pub elf: &'static [u8],
+
+    /// The image ID of the method.
+    pub image_id: Digest,
+
+    /// The path to the method's ELF binary at the time it was built.
+    pub path: &'static str,
+
+    /// The SHA-256 of the build manifest of the guest package that contains
+    /// this method, as a hex string. This is empty for prebuilt ELFs, which
+    /// have no build manifest.
+    pub manifest_sha256: &'static str,
+}
+
+impl GuestMethod {
+    /// Find the method called `name` in `methods`.
+    pub fn find(methods: &'static [GuestMethod], name: &str) -> Option<&'static GuestMethod> {
+        methods.iter().find(|method| method.name == name)
+    }
+}
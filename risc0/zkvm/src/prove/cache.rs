@@ -0,0 +1,179 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [Prover] that caches [SegmentReceipt]s on the local filesystem.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::{Context, Result};
+use risc0_zkp::core::digest::Digest;
+use serde::Serialize;
+
+use super::Prover;
+use crate::{
+    exec::SyscallRecord,
+    receipt::ExitCode,
+    serde::{from_slice, to_vec},
+    session::PageFaults,
+    sha::{Impl, Sha256},
+    Segment, SegmentReceipt, Session, SessionReceipt,
+};
+
+/// The contents of a [Segment] that determine its [SegmentReceipt].
+///
+/// The pre-image is represented by its root, which commits to the full
+/// contents of memory.
+#[derive(Serialize)]
+struct SegmentKey<'a> {
+    prover: String,
+    pre_image_id: Digest,
+    pc: u32,
+    post_image_id: Digest,
+    faults: &'a PageFaults,
+    syscalls: &'a [SyscallRecord],
+    split_insn: Option<u32>,
+    exit_code: ExitCode,
+    input: Digest,
+    po2: usize,
+    index: u32,
+}
+
+/// A [Prover] which stores each [SegmentReceipt] in a local directory, keyed by
+/// a hash of the [Segment] it was proven from, and returns the stored receipt
+/// when the same [Segment] is proven again.
+///
+/// Misses are forwarded to the wrapped [Prover]. Cache entries are
+/// additionally keyed by the name of the wrapped [Prover], so receipts from
+/// different hash suites or HALs are kept apart.
+pub struct CachingProver {
+    inner: Rc<dyn Prover>,
+    dir: PathBuf,
+}
+
+impl CachingProver {
+    /// Construct a [CachingProver] that wraps `inner` and stores receipts in
+    /// `dir`, which is created if it does not already exist.
+    pub fn new(inner: Rc<dyn Prover>, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+        Ok(Self { inner, dir })
+    }
+
+    /// The directory in which receipts are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn segment_key(&self, segment: &Segment) -> Result<Digest> {
+        let key = SegmentKey {
+            prover: self.inner.get_name(),
+            pre_image_id: segment.pre_image.get_root(),
+            pc: segment.pre_image.pc,
+            post_image_id: segment.post_image_id,
+            faults: &segment.faults,
+            syscalls: &segment.syscalls,
+            split_insn: segment.split_insn,
+            exit_code: segment.exit_code,
+            input: segment.input,
+            po2: segment.po2,
+            index: segment.index,
+        };
+        let words: Vec<u32> = to_vec(&key)?;
+        Ok(*Impl::hash_words(&words))
+    }
+
+    fn entry_path(&self, key: &Digest) -> PathBuf {
+        self.dir.join(format!("{key}.receipt"))
+    }
+
+    fn load(&self, segment: &Segment, path: &Path) -> Option<SegmentReceipt> {
+        let bytes = fs::read(path).ok()?;
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        let receipt: SegmentReceipt = match from_slice(&words) {
+            Ok(receipt) => receipt,
+            Err(err) => {
+                log::warn!("Ignoring malformed cache entry {}: {err}", path.display());
+                return None;
+            }
+        };
+
+        // Guard against stale or corrupted entries by checking that the
+        // receipt describes the requested segment.
+        let metadata = receipt.get_metadata().ok()?;
+        if receipt.index != segment.index
+            || metadata.pre.image_id != segment.pre_image.get_root()
+            || metadata.pre.pc != segment.pre_image.pc
+            || metadata.post.image_id != segment.post_image_id
+        {
+            log::warn!("Ignoring mismatched cache entry {}", path.display());
+            return None;
+        }
+        Some(receipt)
+    }
+
+    fn store(&self, path: &Path, receipt: &SegmentReceipt) -> Result<()> {
+        let words: Vec<u32> = to_vec(receipt)?;
+        // Write to a temporary file first so that a concurrent reader never
+        // observes a partial entry.
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytemuck::cast_slice(&words))?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+impl Prover for CachingProver {
+    fn get_name(&self) -> String {
+        format!("cache:{}", self.inner.get_name())
+    }
+
+    fn get_peak_memory_usage(&self) -> usize {
+        self.inner.get_peak_memory_usage()
+    }
+
+    fn prove_session(&self, session: &Session) -> Result<SessionReceipt> {
+        log::debug!("prove_session: {}", self.get_name());
+        let mut segments = Vec::new();
+        for segment_ref in session.segments.iter() {
+            let segment = segment_ref.resolve()?;
+            segments.push(self.prove_segment(&segment)?);
+        }
+        Ok(SessionReceipt {
+            segments,
+            journal: session.journal.clone(),
+            input: session.input.clone(),
+        })
+    }
+
+    fn prove_segment(&self, segment: &Segment) -> Result<SegmentReceipt> {
+        let key = self.segment_key(segment)?;
+        let path = self.entry_path(&key);
+        if let Some(receipt) = self.load(segment, &path) {
+            log::debug!("prove_segment: cache hit {key}");
+            return Ok(receipt);
+        }
+        log::debug!("prove_segment: cache miss {key}");
+        let receipt = self.inner.prove_segment(segment)?;
+        self.store(&path, &receipt)?;
+        Ok(receipt)
+    }
+}
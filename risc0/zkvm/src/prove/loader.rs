@@ -30,7 +30,7 @@ use risc0_zkp::{
     },
     hal::Hal,
     prove::poly_group::PolyGroup,
-    MAX_CYCLES_PO2, MIN_CYCLES_PO2, ZK_CYCLES,
+    ProofParams, MAX_CYCLES_PO2, MIN_CYCLES_PO2, ZK_CYCLES,
 };
 use risc0_zkvm_platform::{memory, WORD_SIZE};
 
@@ -435,8 +435,11 @@ impl Loader {
             // Do interpolate & shift
             hal.batch_interpolate_ntt(&coeffs, code_size);
             hal.zk_shift(&coeffs, code_size);
-            // Make the poly-group & extract the root
-            let code_group = PolyGroup::new(hal, coeffs, code_size, cycles, "code");
+            // Make the poly-group & extract the root. The root doesn't depend on
+            // the number of queries, which only sets how much of the tree is
+            // written to the seal.
+            let queries = ProofParams::default().queries();
+            let code_group = PolyGroup::new(hal, coeffs, code_size, cycles, queries, "code");
             table.push(code_group.merkle.root().clone());
         }
 
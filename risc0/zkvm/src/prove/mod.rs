@@ -33,6 +33,7 @@
 //! # }
 //! ```
 
+mod cache;
 mod exec;
 pub(crate) mod loader;
 mod plonk;
@@ -57,9 +58,11 @@ use risc0_zkp::{
     layout::Buffer,
     prove::{adapter::ProveAdapter, executor::Executor},
     verify::CpuVerifyHal,
+    ProofParams,
 };
 use risc0_zkvm_platform::WORD_SIZE;
 
+pub use self::cache::CachingProver;
 use self::{exec::MachineContext, loader::Loader};
 use crate::{ControlId, Segment, SegmentReceipt, Session, SessionReceipt, CIRCUIT};
 
@@ -122,7 +125,13 @@ pub mod cpu {
     use std::rc::Rc;
 
     use risc0_circuit_rv32im::{cpu::CpuEvalCheck, CircuitImpl};
-    use risc0_zkp::hal::cpu::{BabyBearPoseidonCpuHal, BabyBearSha256CpuHal};
+    use risc0_core::field::baby_bear::BabyBear;
+    use risc0_zkp::{
+        adapter::{CircuitInfo, TapsProvider},
+        core::hash::HashSuite,
+        hal::cpu::{BabyBearPoseidonCpuHal, BabyBearSha256CpuHal, CpuHal},
+        ProofParams,
+    };
 
     use super::HalEval;
     use crate::CIRCUIT;
@@ -155,6 +164,23 @@ pub mod cpu {
         let eval = Rc::new(CpuEvalCheck::new(&CIRCUIT));
         HalEval { hal, eval }
     }
+
+    /// Estimates the peak memory, in bytes, that the CPU HAL uses to prove a
+    /// [Segment](crate::Segment) of `2^po2` cycles with the hash suite `HS`
+    /// and the default [ProofParams].
+    ///
+    /// This is a prediction of what
+    /// [Prover::get_peak_memory_usage](super::Prover::get_peak_memory_usage)
+    /// reports after proving, and can be used to choose a segment size before
+    /// execution.
+    pub fn estimate_peak_memory<HS: HashSuite<BabyBear>>(po2: usize) -> usize {
+        risc0_zkp::prove::estimate_peak_memory::<CpuHal<BabyBear, HS>>(
+            CIRCUIT.get_taps(),
+            &[CircuitImpl::MIX_SIZE, CircuitImpl::OUTPUT_SIZE],
+            &ProofParams::default(),
+            po2,
+        )
+    }
 }
 
 /// A pair of [Hal] and [EvalCheck].
@@ -195,6 +221,7 @@ where
 {
     name: String,
     hal_eval: HalEval<H, E>,
+    params: ProofParams,
 }
 
 impl<H, E> LocalProver<H, E>
@@ -208,8 +235,18 @@ where
         Self {
             name: name.to_string(),
             hal_eval,
+            params: ProofParams::default(),
         }
     }
+
+    /// Use the given [ProofParams] to trade seal size and proving time against
+    /// security. The parameters are recorded in each seal, and verifiers
+    /// reject seals weaker than [ProofParams::DEFAULT] unless they opt in
+    /// with [SessionReceipt::verify_with_params].
+    pub fn with_proof_params(mut self, params: ProofParams) -> Self {
+        self.params = params;
+        self
+    }
 }
 
 impl<H, E> Prover for LocalProver<H, E>
@@ -219,7 +256,11 @@ where
     E: EvalCheck<H>,
 {
     fn get_name(&self) -> String {
-        self.name.clone()
+        if self.params == ProofParams::default() {
+            self.name.clone()
+        } else {
+            format!("{}:{}bit", self.name, self.params.security_bits())
+        }
     }
 
     fn get_peak_memory_usage(&self) -> usize {
@@ -227,7 +268,7 @@ where
     }
 
     fn prove_session(&self, session: &Session) -> Result<SessionReceipt> {
-        log::debug!("prove_session: {}", self.name);
+        log::debug!("prove_session: {}", self.get_name());
         let mut segments = Vec::new();
         for segment_ref in session.segments.iter() {
             let segment = segment_ref.resolve()?;
@@ -236,15 +277,16 @@ where
         let receipt = SessionReceipt {
             segments,
             journal: session.journal.clone(),
+            input: session.input.clone(),
         };
         let image_id = session.segments[0].resolve()?.pre_image.get_root();
         let hal = CpuVerifyHal::<_, H::HashSuite, _>::new(&crate::CIRCUIT);
-        receipt.verify_with_hal(&hal, image_id)?;
+        receipt.verify_with_hal_and_params(&hal, image_id, &self.params)?;
         Ok(receipt)
     }
 
     fn prove_segment(&self, segment: &Segment) -> Result<SegmentReceipt> {
-        log::debug!("prove_segment: {}", self.name);
+        log::debug!("prove_segment: {}", self.get_name());
         let (hal, eval) = (self.hal_eval.hal.as_ref(), &self.hal_eval.eval);
 
         let io = segment.prepare_globals();
@@ -257,6 +299,7 @@ where
 
         let mut adapter = ProveAdapter::new(&mut executor);
         let mut prover = risc0_zkp::prove::Prover::new(hal, CIRCUIT.get_taps());
+        prover.set_proof_params(self.params);
 
         adapter.execute(prover.iop());
 
@@ -289,7 +332,7 @@ where
             index: segment.index,
         };
         let hal = CpuVerifyHal::<_, H::HashSuite, _>::new(&crate::CIRCUIT);
-        receipt.verify_with_hal(&hal)?;
+        receipt.verify_with_hal_and_params(&hal, &self.params)?;
 
         Ok(receipt)
     }
@@ -373,8 +416,12 @@ impl Segment {
 
         // initialize Input
         let mut offset = 0;
-        for i in 0..DIGEST_WORDS * WORD_SIZE {
-            io[offset + i] = Elem::ZERO;
+        let input = self.input.as_words();
+        for i in 0..DIGEST_WORDS {
+            let bytes = input[i].to_le_bytes();
+            for j in 0..WORD_SIZE {
+                io[offset + i * WORD_SIZE + j] = (bytes[j] as u32).into();
+            }
         }
         offset += DIGEST_WORDS * WORD_SIZE;
 
@@ -21,13 +21,14 @@ use risc0_zkp::{
     core::{digest::Digest, hash::blake2b::Blake2bCpuHashSuite},
     hal::cpu::CpuHal,
     verify::VerificationError,
+    ProofParams,
 };
-use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST, MULTI_TEST_ELF, MULTI_TEST_ID};
 use risc0_zkvm_platform::memory::HEAP;
 use serial_test::serial;
 use test_log::test;
 
-use super::{get_prover, LocalProver, Prover};
+use super::{cpu, get_prover, CachingProver, LocalProver, Prover};
 use crate::{
     prove::HalEval,
     serde::{from_slice, to_vec},
@@ -63,6 +64,66 @@ fn hashfn_blake2b() {
     prover.prove_session(&session).unwrap();
 }
 
+#[test]
+fn proof_params() {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let env = ExecutorEnv::builder().add_input(&input).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let segments = session.resolve().unwrap();
+
+    let prove = |params| {
+        let prover = LocalProver::new("cpu", cpu::sha256_hal_eval()).with_proof_params(params);
+        let receipt = prover.prove_segment(&segments[0]).unwrap();
+        assert_eq!(receipt.get_proof_params().unwrap(), params);
+        receipt
+    };
+    let default = prove(ProofParams::DEFAULT);
+    default.verify().unwrap();
+    let fast = prove(ProofParams::FAST);
+    fast.verify_with_params(&ProofParams::FAST).unwrap();
+    assert!(fast.seal.len() < default.seal.len());
+
+    // A seal made with weaker parameters is rejected unless the verifier opts
+    // in to them.
+    assert_eq!(fast.verify(), Err(VerificationError::ProofParamsTooWeak));
+    let prover =
+        LocalProver::new("cpu", cpu::sha256_hal_eval()).with_proof_params(ProofParams::FAST);
+    let receipt = prover.prove_session(&session).unwrap();
+    assert_eq!(
+        receipt.verify(MULTI_TEST_ID),
+        Err(VerificationError::ProofParamsTooWeak)
+    );
+    receipt
+        .verify_with_params(MULTI_TEST_ID, &ProofParams::FAST)
+        .unwrap();
+}
+
+// Finding the proof of work of the conservative preset takes around 2^28
+// SHA-256 compressions.
+#[test]
+#[ignore]
+fn conservative_proof_params() {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let env = ExecutorEnv::builder().add_input(&input).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let prover = LocalProver::new("cpu", cpu::sha256_hal_eval())
+        .with_proof_params(ProofParams::CONSERVATIVE);
+    let receipt = prover.prove_session(&session).unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+    receipt
+        .verify_with_params(MULTI_TEST_ID, &ProofParams::CONSERVATIVE)
+        .unwrap();
+
+    // A default seal is too weak for a verifier that requires 128 bits.
+    let receipt = get_prover("cpu").prove_session(&session).unwrap();
+    assert_eq!(
+        receipt.verify_with_params(MULTI_TEST_ID, &ProofParams::CONSERVATIVE),
+        Err(VerificationError::ProofParamsTooWeak)
+    );
+}
+
 #[test]
 #[cfg_attr(feature = "cuda", serial)]
 fn receipt_serde() {
@@ -87,6 +148,112 @@ fn check_image_id() {
     );
 }
 
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn public_input() {
+    let input = to_vec(&MultiTestSpec::PublicInput).unwrap();
+    let env = ExecutorEnv::builder()
+        .add_input(&input)
+        .public_input(b"public bytes")
+        .build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let mut receipt = session.prove().unwrap();
+    assert_eq!(receipt.input, b"public bytes");
+    receipt.verify(MULTI_TEST_ID).unwrap();
+
+    receipt.input = b"other bytes".to_vec();
+    assert_eq!(
+        receipt.verify(MULTI_TEST_ID).unwrap_err(),
+        VerificationError::InputDigestMismatch
+    );
+}
+
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn verify_receipt_in_guest() {
+    let prove_inner = |spec: &MultiTestSpec| {
+        let env = ExecutorEnv::builder()
+            .add_input(&to_vec(spec).unwrap())
+            .build();
+        Executor::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap()
+            .prove()
+            .unwrap()
+    };
+
+    let run_outer = |inner: &SessionReceipt, image_id: Digest| {
+        let spec = to_vec(&MultiTestSpec::VerifyReceipt {
+            image_id: image_id.into(),
+        })
+        .unwrap();
+        let env = ExecutorEnv::builder()
+            .add_input(&spec)
+            .add_input(&to_vec(inner).unwrap())
+            .build();
+        Executor::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap()
+    };
+
+    // The guest sees how the inner execution ended, so a receipt for a
+    // failed execution is not mistaken for a successful one.
+    for (spec, exit_code) in [
+        (MultiTestSpec::DoNothing, ExitCode::Halted(0)),
+        (MultiTestSpec::Exit { code: 7 }, ExitCode::Halted(7)),
+    ] {
+        let inner = prove_inner(&spec);
+        let session = run_outer(&inner, MULTI_TEST_ID.into());
+        assert_eq!(session.exit_code, ExitCode::Halted(0));
+        let journal: (Vec<u8>, ExitCode) = from_slice(&session.journal).unwrap();
+        assert_eq!(journal, (inner.journal.clone(), exit_code));
+    }
+
+    let inner = prove_inner(&MultiTestSpec::DoNothing);
+    let mut image_id: Digest = MULTI_TEST_ID.into();
+    image_id.as_mut_words()[0] ^= 1;
+    let session = run_outer(&inner, image_id);
+    assert_eq!(session.exit_code, ExitCode::Halted(1));
+    let err: String = from_slice(&session.journal).unwrap();
+    assert_eq!(err, VerificationError::ImageVerificationError.to_string());
+}
+
+#[test]
+fn guest_method_registry() {
+    let method = risc0_zkvm_methods::method("multi_test").unwrap();
+    assert_eq!(method.elf, MULTI_TEST_ELF);
+    assert_eq!(method.image_id, Digest::from(MULTI_TEST_ID));
+    assert_eq!(*method, MULTI_TEST);
+    assert!(risc0_zkvm_methods::METHODS.contains(&MULTI_TEST));
+    assert!(risc0_zkvm_methods::method("missing").is_none());
+}
+
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn caching_prover() {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let env = ExecutorEnv::builder().add_input(&input).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let prover = CachingProver::new(get_prover("$default"), cache_dir.path()).unwrap();
+    let receipt = prover.prove_session(&session).unwrap();
+    assert_eq!(
+        std::fs::read_dir(cache_dir.path()).unwrap().count(),
+        receipt.segments.len()
+    );
+
+    // A second prover sharing the directory must be served from the cache.
+    let prover = CachingProver::new(get_prover("$default"), cache_dir.path()).unwrap();
+    let cached = prover.prove_session(&session).unwrap();
+    assert_eq!(cached, receipt);
+    cached.verify(MULTI_TEST_ID).unwrap();
+}
+
 #[test]
 #[serial]
 fn sha_basics() {
@@ -64,14 +64,29 @@
 //! [serde](crate::serde) module, which can be used to read data from the
 //! journal as the same type it was written to the journal. If you prefer, you
 //! can also directly access the [SessionReceipt::journal] as a `Vec<u8>`.
+//!
+//! Input that the verifier should see, but that the guest does not need to
+//! echo into the journal, can be provided as public input with
+//! `ExecutorEnvBuilder::public_input`. It is carried in
+//! [SessionReceipt::input], and its digest is checked against the one recorded
+//! in each segment.
+//!
+//! # Format
+//! Adding [SessionReceipt::input] changed the serialized form of
+//! [SessionReceipt]. Positional encodings, such as the zkVM
+//! [serde](crate::serde) module or bincode, can not read receipts written by
+//! earlier versions, nor can earlier versions read the new ones.
 
 use alloc::vec::Vec;
 
 use anyhow::Result;
 use hex::FromHex;
-use risc0_circuit_rv32im::layout;
+use risc0_circuit_rv32im::{layout, CircuitImpl};
 use risc0_core::field::baby_bear::BabyBearElem;
-use risc0_zkp::{core::digest::Digest, layout::Buffer, verify::VerificationError, MIN_CYCLES_PO2};
+use risc0_zkp::{
+    adapter::CircuitInfo, core::digest::Digest, layout::Buffer, verify::VerificationError,
+    ProofParams, MIN_CYCLES_PO2,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -144,6 +159,16 @@ pub struct SessionReceipt {
     /// This data is cryptographically authenticated in
     /// [SessionReceipt::verify].
     pub journal: Vec<u8>,
+
+    /// The public input provided by the host to this Session.
+    ///
+    /// Its digest is bound into each segment, and checked in
+    /// [SessionReceipt::verify].
+    ///
+    /// Defaulting this field only lets self-describing formats read older
+    /// receipts; see the [module docs](self#format).
+    #[serde(default)]
+    pub input: Vec<u8>,
 }
 
 /// A receipt attesting to the execution of a Segment.
@@ -167,7 +192,12 @@ impl SessionReceipt {
     /// Segment has a valid receipt, and validates that these [SegmentReceipt]s
     /// stitch together correctly, and that the initial memory image matches the
     /// given `_image_id` parameter.
-    #[cfg(not(target_os = "zkvm"))]
+    ///
+    /// Each segment must have been proven with [ProofParams] at least as strong
+    /// as [ProofParams::DEFAULT]; see [SessionReceipt::verify_with_params].
+    ///
+    /// Inside the guest, this uses the SHA-256 accelerator, so that a guest can
+    /// verify receipts produced by other guests.
     pub fn verify(&self, image_id: impl Into<Digest>) -> Result<(), VerificationError> {
         use risc0_zkp::core::hash::sha::Sha256HashSuite;
         let hal =
@@ -177,6 +207,25 @@ impl SessionReceipt {
         self.verify_with_hal(&hal, image_id)
     }
 
+    /// Verifies the integrity of this receipt, like [SessionReceipt::verify],
+    /// and that each segment was proven with [ProofParams] at least as strong
+    /// as `min_params`.
+    ///
+    /// [SessionReceipt::verify] requires [ProofParams::DEFAULT], so this is
+    /// needed to accept receipts proven with [ProofParams::FAST].
+    pub fn verify_with_params(
+        &self,
+        image_id: impl Into<Digest>,
+        min_params: &ProofParams,
+    ) -> Result<(), VerificationError> {
+        use risc0_zkp::core::hash::sha::Sha256HashSuite;
+        let hal =
+            risc0_zkp::verify::CpuVerifyHal::<_, Sha256HashSuite<_, crate::sha::Impl>, _>::new(
+                &crate::CIRCUIT,
+            );
+        self.verify_with_hal_and_params(&hal, image_id, min_params)
+    }
+
     /// Verifies the integrity of this receipt.
     ///
     /// Uses the ZKP system to cryptographically verify that each constituent
@@ -188,6 +237,19 @@ impl SessionReceipt {
         hal: &H,
         image_id: impl Into<Digest>,
     ) -> Result<(), VerificationError>
+    where
+        H: risc0_zkp::verify::VerifyHal<Elem = BabyBearElem>,
+        H::HashFn: ControlId,
+    {
+        self.verify_with_hal_and_params(hal, image_id, &ProofParams::DEFAULT)
+    }
+
+    pub(crate) fn verify_with_hal_and_params<H>(
+        &self,
+        hal: &H,
+        image_id: impl Into<Digest>,
+        min_params: &ProofParams,
+    ) -> Result<(), VerificationError>
     where
         H: risc0_zkp::verify::VerifyHal<Elem = BabyBearElem>,
         H::HashFn: ControlId,
@@ -197,22 +259,29 @@ impl SessionReceipt {
             .as_slice()
             .split_last()
             .ok_or(VerificationError::ReceiptFormatError)?;
+        let input = public_input_digest(&self.input);
         let mut prev_image_id = image_id.into();
         for receipt in receipts {
-            receipt.verify_with_hal(hal)?;
+            receipt.verify_with_hal_and_params(hal, min_params)?;
             let metadata = receipt.get_metadata()?;
             if prev_image_id != metadata.pre.image_id {
                 return Err(VerificationError::ImageVerificationError);
             }
+            if metadata.input != input {
+                return Err(VerificationError::InputDigestMismatch);
+            }
             // assert_eq!(metadata.exit_code, ExitCode::SystemSplit);
             prev_image_id = metadata.post.image_id;
         }
-        final_receipt.verify_with_hal(hal)?;
+        final_receipt.verify_with_hal_and_params(hal, min_params)?;
         let metadata = final_receipt.get_metadata()?;
         // log::debug!("metadata: {metadata:#?}");
         if prev_image_id != metadata.pre.image_id {
             return Err(VerificationError::ImageVerificationError);
         }
+        if metadata.input != input {
+            return Err(VerificationError::InputDigestMismatch);
+        }
 
         let digest = Sha256::digest(&self.journal);
         let digest_words: &[u32] = bytemuck::cast_slice(digest.as_slice());
@@ -239,6 +308,15 @@ impl SessionReceipt {
     }
 }
 
+/// Returns the digest of a public input, as recorded in the Input global of
+/// each segment: zero if there is no public input, and its SHA-256 otherwise.
+pub(crate) fn public_input_digest(input: &[u8]) -> Digest {
+    if input.is_empty() {
+        return Digest::default();
+    }
+    Digest::try_from(Sha256::digest(input).as_slice()).unwrap()
+}
+
 impl SegmentReceipt {
     /// Get the [ReceiptMetadata] associated with the current receipt.
     pub fn get_metadata(&self) -> Result<ReceiptMetadata, VerificationError> {
@@ -246,11 +324,19 @@ impl SegmentReceipt {
         ReceiptMetadata::decode(layout::OutBuffer(elems))
     }
 
+    /// Get the [ProofParams] that the seal of this receipt was made with.
+    pub fn get_proof_params(&self) -> Result<ProofParams, VerificationError> {
+        let header = self
+            .seal
+            .get(CircuitImpl::OUTPUT_SIZE)
+            .ok_or(VerificationError::ReceiptFormatError)?;
+        ProofParams::from_seal_header(*header)
+    }
+
     /// Verifies the integrity of this receipt.
     ///
     /// Uses the ZKP system to cryptographically verify that the seal does
     /// validly indicate that this Segment was executed faithfully.
-    #[cfg(not(target_os = "zkvm"))]
     pub fn verify(&self) -> Result<(), VerificationError> {
         use risc0_zkp::core::hash::sha::Sha256HashSuite;
         let hal =
@@ -260,11 +346,36 @@ impl SegmentReceipt {
         self.verify_with_hal(&hal)
     }
 
+    /// Verifies the integrity of this receipt, like [SegmentReceipt::verify],
+    /// and that the seal was made with [ProofParams] at least as strong as
+    /// `min_params`.
+    pub fn verify_with_params(&self, min_params: &ProofParams) -> Result<(), VerificationError> {
+        use risc0_zkp::core::hash::sha::Sha256HashSuite;
+        let hal =
+            risc0_zkp::verify::CpuVerifyHal::<_, Sha256HashSuite<_, crate::sha::Impl>, _>::new(
+                &crate::CIRCUIT,
+            );
+        self.verify_with_hal_and_params(&hal, min_params)
+    }
+
     /// Verifies the integrity of this receipt.
     ///
     /// Uses the ZKP system to cryptographically verify that the seal does
-    /// validly indicate that this Segment was executed faithfully.
+    /// validly indicate that this Segment was executed faithfully, with
+    /// [ProofParams] at least as strong as [ProofParams::DEFAULT].
     pub fn verify_with_hal<H>(&self, hal: &H) -> Result<(), VerificationError>
+    where
+        H: risc0_zkp::verify::VerifyHal<Elem = BabyBearElem>,
+        H::HashFn: ControlId,
+    {
+        self.verify_with_hal_and_params(hal, &ProofParams::DEFAULT)
+    }
+
+    pub(crate) fn verify_with_hal_and_params<H>(
+        &self,
+        hal: &H,
+        min_params: &ProofParams,
+    ) -> Result<(), VerificationError>
     where
         H: risc0_zkp::verify::VerifyHal<Elem = BabyBearElem>,
         H::HashFn: ControlId,
@@ -281,7 +392,7 @@ impl SegmentReceipt {
             }
             Err(VerificationError::ControlVerificationError)
         };
-        risc0_zkp::verify::verify(hal, &CIRCUIT, &self.seal, check_code)
+        risc0_zkp::verify::verify_with_params(hal, &CIRCUIT, &self.seal, check_code, min_params)
     }
 
     /// Extracts the seal from the receipt, as a series of bytes.
@@ -0,0 +1,258 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed calls from the guest to services provided by the host.
+//!
+//! A [Service] pairs a request type and a response type, both serialized with
+//! [crate::serde], with a system call and a version. Services are usually
+//! defined with [rpc_service!](crate::rpc_service), in a crate shared by the
+//! guest and the host:
+//!
+//! ```ignore
+//! risc0_zkvm::rpc_service! {
+//!     /// Looks up the balance of an account.
+//!     pub GetBalance(version = 1): String => u64
+//! }
+//! ```
+//!
+//! The guest calls the service with the generated stub:
+//!
+//! ```ignore
+//! let balance = GetBalance::call(&"alice".to_string()).unwrap();
+//! ```
+//!
+//! and the host provides the handler when building the `ExecutorEnv`:
+//!
+//! ```ignore
+//! let env = ExecutorEnv::builder()
+//!     .service::<GetBalance>(|account| Ok(balances[&account]))
+//!     .build();
+//! ```
+//!
+//! Each call sends the version of the service the guest was built against,
+//! and the host rejects calls whose version does not match its own.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+pub use risc0_zkvm_platform::syscall::SyscallName;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::serde::{from_slice, to_vec};
+
+const STATUS_OK: u32 = 0;
+const STATUS_VERSION_MISMATCH: u32 = 1;
+const STATUS_MALFORMED_REQUEST: u32 = 2;
+const STATUS_FAILED: u32 = 3;
+
+/// A service provided by the host, which the guest calls with a
+/// [Service::Request] and which replies with a [Service::Response].
+pub trait Service {
+    /// The system call that carries calls to this service.
+    const SYSCALL: SyscallName;
+
+    /// The version of the request and response types. The host rejects calls
+    /// from a guest built against a different version.
+    const VERSION: u32;
+
+    /// The type sent by the guest.
+    type Request: Serialize + DeserializeOwned;
+
+    /// The type returned by the host.
+    type Response: Serialize + DeserializeOwned;
+}
+
+/// Errors returned to the guest by [call].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RpcError {
+    /// The guest and host were built against different versions of the
+    /// service.
+    VersionMismatch {
+        /// The version the guest was built against.
+        guest: u32,
+        /// The version the host was built against.
+        host: u32,
+    },
+
+    /// The host could not deserialize the request.
+    MalformedRequest,
+
+    /// The guest could not deserialize the response.
+    MalformedResponse,
+
+    /// The host handler returned an error, with the given message.
+    Failed(String),
+}
+
+impl Display for RpcError {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        match self {
+            Self::VersionMismatch { guest, host } => write!(
+                f,
+                "Service version mismatch: guest has version {guest}, host has version {host}"
+            ),
+            Self::MalformedRequest => f.write_str("The host could not decode the request"),
+            Self::MalformedResponse => f.write_str("The guest could not decode the response"),
+            Self::Failed(msg) => write!(f, "The host handler failed: {msg}"),
+        }
+    }
+}
+
+/// Call the service `S` on the host.
+pub fn call<S: Service>(request: &S::Request) -> Result<S::Response, RpcError> {
+    let mut words = vec![S::VERSION];
+    words.extend(to_vec(request).map_err(|_| RpcError::MalformedRequest)?);
+    let reply: &[u32] = crate::guest::env::send_recv_slice(S::SYSCALL, &words);
+    decode_reply::<S>(reply)
+}
+
+/// Handle a call to the service `S` using `handler`, and return the reply to
+/// send to the guest.
+#[cfg(feature = "prove")]
+pub(crate) fn handle_call<S: Service>(
+    words: &[u32],
+    handler: impl Fn(S::Request) -> anyhow::Result<S::Response>,
+) -> Vec<u32> {
+    let (version, request) = match words.split_first() {
+        Some(split) => split,
+        None => return vec![STATUS_MALFORMED_REQUEST],
+    };
+    if *version != S::VERSION {
+        return vec![STATUS_VERSION_MISMATCH, *version, S::VERSION];
+    }
+    let request = match from_slice(request) {
+        Ok(request) => request,
+        Err(_) => return vec![STATUS_MALFORMED_REQUEST],
+    };
+    let (status, payload) = match handler(request) {
+        Ok(response) => (STATUS_OK, to_vec(&response)),
+        Err(err) => (STATUS_FAILED, to_vec(&err.to_string())),
+    };
+    let mut reply = vec![status];
+    reply.extend(payload.expect("Failed to serialize service reply"));
+    reply
+}
+
+fn decode_reply<S: Service>(reply: &[u32]) -> Result<S::Response, RpcError> {
+    match reply.split_first() {
+        Some((&STATUS_OK, payload)) => from_slice(payload).map_err(|_| RpcError::MalformedResponse),
+        Some((&STATUS_VERSION_MISMATCH, &[guest, host])) => {
+            Err(RpcError::VersionMismatch { guest, host })
+        }
+        Some((&STATUS_MALFORMED_REQUEST, [])) => Err(RpcError::MalformedRequest),
+        Some((&STATUS_FAILED, payload)) => Err(RpcError::Failed(
+            from_slice(payload).map_err(|_| RpcError::MalformedResponse)?,
+        )),
+        _ => Err(RpcError::MalformedResponse),
+    }
+}
+
+/// Define a [Service](crate::rpc::Service) along with a `call` function that
+/// invokes it from the guest.
+///
+/// The system call is named after the module path and the name of the
+/// service.
+///
+/// ```ignore
+/// risc0_zkvm::rpc_service! {
+///     /// Looks up the balance of an account.
+///     pub GetBalance(version = 1): String => u64
+/// }
+/// ```
+#[macro_export]
+macro_rules! rpc_service {
+    ($(#[$meta:meta])*
+     $vis:vis $name:ident(version = $version:literal): $request:ty => $response:ty) => {
+        $(#[$meta])*
+        $vis struct $name;
+
+        impl $crate::rpc::Service for $name {
+            const SYSCALL: $crate::rpc::SyscallName = unsafe {
+                $crate::rpc::SyscallName::from_bytes_with_nul(
+                    concat!(module_path!(), "::", stringify!($name), "\0").as_ptr(),
+                )
+            };
+            const VERSION: u32 = $version;
+            type Request = $request;
+            type Response = $response;
+        }
+
+        impl $name {
+            /// Call this service on the host.
+            #[allow(dead_code)]
+            $vis fn call(
+                request: &$request,
+            ) -> ::core::result::Result<$response, $crate::rpc::RpcError> {
+                $crate::rpc::call::<Self>(request)
+            }
+        }
+    };
+}
+
+#[cfg(all(test, feature = "prove"))]
+mod tests {
+    use anyhow::bail;
+
+    use super::*;
+
+    rpc_service! {
+        Double(version = 2): Vec<u32> => Vec<u32>
+    }
+
+    fn double(request: Vec<u32>) -> anyhow::Result<Vec<u32>> {
+        if request.is_empty() {
+            bail!("nothing to double");
+        }
+        Ok(request.iter().map(|x| x * 2).collect())
+    }
+
+    fn request(version: u32, request: &[u32]) -> Vec<u32> {
+        let mut words = vec![version];
+        words.extend(to_vec(&request.to_vec()).unwrap());
+        words
+    }
+
+    #[test]
+    fn round_trip() {
+        let reply = handle_call::<Double>(&request(2, &[1, 2, 3]), double);
+        assert_eq!(decode_reply::<Double>(&reply), Ok(vec![2, 4, 6]));
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let reply = handle_call::<Double>(&request(1, &[1]), double);
+        assert_eq!(
+            decode_reply::<Double>(&reply),
+            Err(RpcError::VersionMismatch { guest: 1, host: 2 })
+        );
+    }
+
+    #[test]
+    fn handler_error() {
+        let reply = handle_call::<Double>(&request(2, &[]), double);
+        assert_eq!(
+            decode_reply::<Double>(&reply),
+            Err(RpcError::Failed("nothing to double".into()))
+        );
+    }
+
+    #[test]
+    fn malformed_request() {
+        let reply = handle_call::<Double>(&[2], double);
+        assert_eq!(
+            decode_reply::<Double>(&reply),
+            Err(RpcError::MalformedRequest)
+        );
+    }
+}
@@ -18,7 +18,10 @@ use bytemuck::Pod;
 use risc0_zkvm_platform::WORD_SIZE;
 use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
 
-use super::err::{Error, Result};
+use super::{
+    err::{Error, Result},
+    tag,
+};
 use crate::align_up;
 
 /// A reader for reading streams with serialized word-based data
@@ -74,12 +77,32 @@ impl WordRead for &[u32] {
 /// `T`.
 pub fn from_slice<T: DeserializeOwned, P: Pod>(slice: &[P]) -> Result<T> {
     let mut deserializer = Deserializer::new(bytemuck::cast_slice(slice));
-    T::deserialize(&mut deserializer)
+    T::deserialize(&mut deserializer).map_err(|err| err.at(deserializer.position()))
 }
 
+/// Deserialize a slice in the self-describing encoding into the specified
+/// type.
+///
+/// Like [from_slice], but for data written with
+/// [to_vec_tagged](super::to_vec_tagged).
+pub fn from_slice_tagged<T: DeserializeOwned, P: Pod>(slice: &[P]) -> Result<T> {
+    let mut deserializer = Deserializer::new_tagged(bytemuck::cast_slice(slice));
+    T::deserialize(&mut deserializer).map_err(|err| err.at(deserializer.position()))
+}
+
+// The deepest that options, sequences and maps may be nested in the
+// self-describing encoding, so that malformed input can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
 /// Enables deserializing from a WordRead
 pub struct Deserializer<'de, R: WordRead + 'de> {
     reader: R,
+    position: usize,
+    tagged: bool,
+    // A tag read ahead to check for the end of a sequence or map.
+    peeked: Option<u32>,
+    // How many options, sequences and maps enclose the current value.
+    depth: usize,
     phantom: core::marker::PhantomData<&'de ()>,
 }
 
@@ -115,6 +138,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::de::VariantAccess<'de> for &'a mut Deser
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
+        if self.tagged {
+            return serde::de::Deserialize::deserialize(self);
+        }
         Ok(())
     }
 
@@ -131,6 +157,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::de::VariantAccess<'de> for &'a mut Deser
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value> {
+        if self.tagged {
+            return serde::de::Deserializer::deserialize_any(self, visitor);
+        }
         serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
     }
 }
@@ -140,6 +169,10 @@ impl<'de, 'a, R: WordRead + 'de> serde::de::EnumAccess<'de> for &'a mut Deserial
     type Variant = Self;
 
     fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        if self.tagged {
+            let val = DeserializeSeed::deserialize(seed, &mut *self)?;
+            return Ok((val, self));
+        }
         let tag = self.try_take_word()?;
         let val = DeserializeSeed::deserialize(seed, tag.into_deserializer())?;
         Ok((val, self))
@@ -175,6 +208,61 @@ impl<'a, 'de: 'a, R: WordRead + 'de> serde::de::MapAccess<'de> for MapAccess<'a,
     }
 }
 
+// Accesses the elements of a sequence or map in the self-describing encoding,
+// which continue up to an END tag.
+struct TaggedAccess<'a, 'de, R: WordRead + 'de> {
+    deserializer: &'a mut Deserializer<'de, R>,
+    done: bool,
+}
+
+impl<'a, 'de, R: WordRead + 'de> TaggedAccess<'a, 'de, R> {
+    fn next_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.deserializer.peek_tag()? == tag::END {
+            self.deserializer.peeked = None;
+            self.done = true;
+            return Ok(None);
+        }
+        Ok(Some(DeserializeSeed::deserialize(
+            seed,
+            &mut *self.deserializer,
+        )?))
+    }
+
+    // Checks that the visitor consumed every element.
+    fn finish(self) -> Result<()> {
+        if !self.done && self.deserializer.take_tag()? != tag::END {
+            return Err(Error::DeserializeBadTag);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'de, R: WordRead + 'de> serde::de::SeqAccess<'de> for TaggedAccess<'a, 'de, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.next_seed(seed)
+    }
+}
+
+impl<'a, 'de, R: WordRead + 'de> serde::de::MapAccess<'de> for TaggedAccess<'a, 'de, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        self.next_seed(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        DeserializeSeed::deserialize(seed, &mut *self.deserializer)
+    }
+}
+
 impl<'de, R: WordRead + 'de> Deserializer<'de, R> {
     /// Construct a Deserializer
     ///
@@ -182,13 +270,43 @@ impl<'de, R: WordRead + 'de> Deserializer<'de, R> {
     pub fn new(reader: R) -> Self {
         Deserializer {
             reader,
+            position: 0,
+            tagged: false,
+            peeked: None,
+            depth: 0,
             phantom: core::marker::PhantomData,
         }
     }
 
+    /// Construct a Deserializer for the self-describing encoding
+    ///
+    /// Creates a deserializer for data written by [Serializer::new_tagged],
+    /// which supports `deserialize_any`. Values nested more than 128 deep
+    /// fail with [Error::DeserializeTooDeep].
+    ///
+    /// [Serializer::new_tagged]: super::Serializer::new_tagged
+    pub fn new_tagged(reader: R) -> Self {
+        Deserializer {
+            tagged: true,
+            ..Self::new(reader)
+        }
+    }
+
+    /// Returns the number of words read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn read_padded_bytes(&mut self, bytes: &mut [u8]) -> Result<()> {
+        self.reader.read_padded_bytes(bytes)?;
+        self.position += align_up(bytes.len(), WORD_SIZE) / WORD_SIZE;
+        Ok(())
+    }
+
     fn try_take_word(&mut self) -> Result<u32> {
         let mut val = 0u32;
         self.reader.read_words(core::slice::from_mut(&mut val))?;
+        self.position += 1;
         Ok(val)
     }
 
@@ -197,6 +315,73 @@ impl<'de, R: WordRead + 'de> Deserializer<'de, R> {
         let high = self.try_take_word()? as u64;
         Ok(low | high << 32)
     }
+
+    fn try_take_bytes(&mut self) -> Result<Vec<u8>> {
+        // The length comes from the input, so it is read a chunk at a time
+        // rather than allocated up front, to keep a bogus length from
+        // exhausting memory before the input runs out.
+        const CHUNK_BYTES: usize = 4096;
+        let len_bytes = self.try_take_word()? as usize;
+        let mut bytes: Vec<u8> = Vec::with_capacity(len_bytes.min(CHUNK_BYTES));
+        while bytes.len() < len_bytes {
+            let start = bytes.len();
+            // TODO: Can we use MaybeUninit here instead of zeroing out?
+            // The documentation for sys::io::Read implies that it's not
+            // safe; is there another way to not do double writes here?
+            bytes.resize(start + (len_bytes - start).min(CHUNK_BYTES), 0);
+            self.read_padded_bytes(&mut bytes[start..])?;
+        }
+        Ok(bytes)
+    }
+
+    fn try_take_string(&mut self) -> Result<String> {
+        String::from_utf8(self.try_take_bytes()?).map_err(|_| Error::DeserializeBadUtf8)
+    }
+
+    fn take_tag(&mut self) -> Result<u32> {
+        match self.peeked.take() {
+            Some(tag) => Ok(tag),
+            None => self.try_take_word(),
+        }
+    }
+
+    fn peek_tag(&mut self) -> Result<u32> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.try_take_word()?);
+        }
+        Ok(self.peeked.unwrap())
+    }
+
+    // Runs `f` one level deeper, or fails if that would exceed [MAX_DEPTH].
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::DeserializeTooDeep);
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn visit_tagged_seq<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value> {
+        let mut access = TaggedAccess {
+            deserializer: self,
+            done: false,
+        };
+        let val = visitor.visit_seq(&mut access)?;
+        access.finish()?;
+        Ok(val)
+    }
+
+    fn visit_tagged_map<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value> {
+        let mut access = TaggedAccess {
+            deserializer: self,
+            done: false,
+        };
+        let val = visitor.visit_map(&mut access)?;
+        access.finish()?;
+        Ok(val)
+    }
 }
 
 impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializer<'de, R> {
@@ -206,17 +391,47 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
         false
     }
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(Error::NotSupported)
+        if !self.tagged {
+            return Err(Error::NotSupported);
+        }
+        match self.take_tag()? {
+            tag::UNIT => visitor.visit_unit(),
+            tag::BOOL => match self.try_take_word()? {
+                0 => visitor.visit_bool(false),
+                1 => visitor.visit_bool(true),
+                _ => Err(Error::DeserializeBadBool),
+            },
+            tag::I32 => visitor.visit_i32(self.try_take_word()? as i32),
+            tag::I64 => visitor.visit_i64(self.try_take_dword()? as i64),
+            tag::U32 => visitor.visit_u32(self.try_take_word()?),
+            tag::U64 => visitor.visit_u64(self.try_take_dword()?),
+            tag::F32 => visitor.visit_f32(f32::from_bits(self.try_take_word()?)),
+            tag::F64 => visitor.visit_f64(f64::from_bits(self.try_take_dword()?)),
+            tag::CHAR => {
+                let c = char::from_u32(self.try_take_word()?).ok_or(Error::DeserializeBadChar)?;
+                visitor.visit_char(c)
+            }
+            tag::STR => visitor.visit_string(self.try_take_string()?),
+            tag::BYTES => visitor.visit_byte_buf(self.try_take_bytes()?),
+            tag::NONE => visitor.visit_none(),
+            tag::SOME => self.nested(|de| visitor.visit_some(de)),
+            tag::SEQ => self.nested(|de| de.visit_tagged_seq(visitor)),
+            tag::MAP => self.nested(|de| de.visit_tagged_map(visitor)),
+            _ => Err(Error::DeserializeBadTag),
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         let val = match self.try_take_word()? {
             0 => false,
             1 => true,
@@ -229,20 +444,23 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.try_take_word()? as i32)
+        self.deserialize_i32(visitor)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.try_take_word()? as i32)
+        self.deserialize_i32(visitor)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_i32(self.try_take_word()? as i32)
     }
 
@@ -250,6 +468,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_i64(self.try_take_dword()? as i64)
     }
 
@@ -257,20 +478,23 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.try_take_word()?)
+        self.deserialize_u32(visitor)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.try_take_word()?)
+        self.deserialize_u32(visitor)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_u32(self.try_take_word()?)
     }
 
@@ -278,6 +502,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_u64(self.try_take_dword()?)
     }
 
@@ -285,6 +512,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_f32(f32::from_bits(self.try_take_word()?))
     }
 
@@ -292,6 +522,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_f64(f64::from_bits(self.try_take_dword()?))
     }
 
@@ -299,6 +532,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         let c = char::from_u32(self.try_take_word()?).ok_or(Error::DeserializeBadChar)?;
         visitor.visit_char(c)
     }
@@ -307,14 +543,10 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
-        let len_bytes = self.try_take_word()? as usize;
-        let mut bytes: Vec<u8> = Vec::with_capacity(len_bytes);
-        // TODO: Can we use MaybeUninit here instead of zeroing out?
-        // The documentation for sys::io::Read implies that it's not
-        // safe; is there another way to not do double writes here?
-        bytes.resize(len_bytes, 0);
-        self.reader.read_padded_bytes(&mut bytes)?;
-        visitor.visit_string(String::from_utf8(bytes).map_err(|_| Error::DeserializeBadChar)?)
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
+        visitor.visit_string(self.try_take_string()?)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
@@ -328,14 +560,10 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
-        let len_bytes = self.try_take_word()? as usize;
-        let mut bytes: Vec<u8> = Vec::with_capacity(len_bytes);
-        // TODO: Can we use MaybeUninit here instead of zeroing out?
-        // The documentation for sys::io::Read implies that it's not
-        // safe; is there another way to not do double writes here?
-        bytes.resize(len_bytes, 0);
-        self.reader.read_padded_bytes(&mut bytes)?;
-        visitor.visit_byte_buf(bytes)
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
+        visitor.visit_byte_buf(self.try_take_bytes()?)
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
@@ -349,6 +577,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         match self.try_take_word()? {
             0 => visitor.visit_none(),
             1 => visitor.visit_some(self),
@@ -360,6 +591,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_unit()
     }
 
@@ -381,6 +615,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         let len = self.try_take_word()? as usize;
         visitor.visit_seq(SeqAccess {
             deserializer: self,
@@ -392,6 +629,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         visitor.visit_seq(SeqAccess {
             deserializer: self,
             len,
@@ -414,6 +654,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         let len = self.try_take_word()? as usize;
         visitor.visit_map(MapAccess {
             deserializer: self,
@@ -430,6 +673,9 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         self.deserialize_tuple(fields.len(), visitor)
     }
 
@@ -442,27 +688,51 @@ impl<'de, 'a, R: WordRead + 'de> serde::Deserializer<'de> for &'a mut Deserializ
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(self)
+        if !self.tagged {
+            return visitor.visit_enum(self);
+        }
+        match self.take_tag()? {
+            // A unit variant, encoded as just its name.
+            tag::STR => {
+                let variant = self.try_take_string()?;
+                visitor.visit_enum(variant.as_str().into_deserializer())
+            }
+            // A map from the variant name to its contents.
+            tag::MAP => self.nested(|de| {
+                let val = visitor.visit_enum(&mut *de)?;
+                if de.take_tag()? != tag::END {
+                    return Err(Error::DeserializeBadTag);
+                }
+                Ok(val)
+            }),
+            _ => Err(Error::DeserializeBadTag),
+        }
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         Err(Error::NotSupported)
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.tagged {
+            return self.deserialize_any(visitor);
+        }
         Err(Error::NotSupported)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use alloc::string::String;
+    use alloc::{boxed::Box, string::String};
 
     use serde::{Deserialize, Serialize};
 
@@ -561,4 +831,83 @@ mod tests {
         };
         assert_eq!(expected, from_slice(&words).unwrap());
     }
+
+    #[test]
+    fn test_error_position() {
+        let words = [1, 0, 2];
+        assert_eq!(
+            from_slice::<(bool, bool, bool), _>(&words),
+            Err(Error::DeserializeAt {
+                offset: 3,
+                error: Box::new(Error::DeserializeBadBool),
+            })
+        );
+        let err = from_slice::<(bool, String), _>(&[1, 5]).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeUnexpectedEnd);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |depth: usize| {
+            let mut words = vec![tag::SOME; depth];
+            words.push(tag::UNIT);
+            from_slice_tagged::<serde::de::IgnoredAny, _>(&words)
+        };
+        nested(MAX_DEPTH).unwrap();
+        let err = nested(MAX_DEPTH + 1).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeTooDeep);
+
+        let mut words = vec![tag::SEQ; MAX_DEPTH + 1];
+        words.extend(vec![tag::END; MAX_DEPTH + 1]);
+        let err = from_slice_tagged::<serde::de::IgnoredAny, _>(&words).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeTooDeep);
+
+        // Recursive types nest through their enum variants.
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        enum Expr {
+            Neg(Box<Expr>),
+            Lit(u32),
+        }
+
+        let nested = |depth: usize| {
+            let expr = (1..depth).fold(Expr::Lit(7), |expr, _| Expr::Neg(Box::new(expr)));
+            let words = crate::serde::to_vec_tagged(&expr).unwrap();
+            (expr, from_slice_tagged::<Expr, _>(&words))
+        };
+        let (expr, decoded) = nested(MAX_DEPTH);
+        assert_eq!(decoded.unwrap(), expr);
+        let err = nested(MAX_DEPTH + 1).1.unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeTooDeep);
+    }
+
+    #[test]
+    fn test_huge_length() {
+        let err = from_slice::<String, _>(&[u32::MAX, 0x64636261]).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeUnexpectedEnd);
+
+        // Lengths spanning several chunks still round trip.
+        let long = "abcdefg".repeat(1000);
+        let encoded = crate::serde::to_vec(&long).unwrap();
+        assert_eq!(from_slice::<String, _>(&encoded).unwrap(), long);
+    }
+
+    #[test]
+    fn test_custom_error() {
+        #[derive(Debug)]
+        struct Even(u32);
+
+        impl<'de> Deserialize<'de> for Even {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let val = u32::deserialize(deserializer)?;
+                if val % 2 != 0 {
+                    return Err(serde::de::Error::custom("odd value"));
+                }
+                Ok(Even(val))
+            }
+        }
+
+        let err = from_slice::<Even, _>(&[3]).unwrap_err();
+        assert_eq!(err.kind(), &Error::Custom("odd value".into()));
+        assert_eq!(err.to_string(), "odd value (after reading 1 words)");
+    }
 }
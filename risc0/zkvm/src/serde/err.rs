@@ -12,41 +12,86 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
 use core::fmt::{Display, Formatter};
 
-// use alloc::string::{String, ToString};
-
 #[derive(Clone, Debug, Eq, PartialEq)]
 /// Errors used by Serde
+#[non_exhaustive]
 pub enum Error {
+    /// A custom error message, such as one from a `Serialize` or
+    /// `Deserialize` implementation rejecting a value
+    Custom(String),
     /// Found a bool that wasn't 0 or 1
     DeserializeBadBool,
     /// Found an invalid unicode char
     DeserializeBadChar,
     /// Found an Option discriminant that wasn't 0 or 1
     DeserializeBadOption,
+    /// Found a type tag that wasn't expected in the self-describing encoding
+    DeserializeBadTag,
     /// Tried to parse invalid utf-8
     DeserializeBadUtf8,
     /// Unexpected end during deserialization
     DeserializeUnexpectedEnd,
+    /// Found values nested too deeply in the self-describing encoding
+    DeserializeTooDeep,
+    /// Deserialization failed after reading the given number of words
+    DeserializeAt {
+        /// The number of words read before the error occurred
+        offset: usize,
+        /// The error that occurred
+        error: Box<Error>,
+    },
     /// Not supported
     NotSupported,
     /// The serialize buffer is full
     SerializeBufferFull,
 }
 
+impl Error {
+    /// Returns the error that occurred, without the position information of
+    /// [Error::DeserializeAt].
+    pub fn kind(&self) -> &Error {
+        match self {
+            Self::DeserializeAt { error, .. } => error,
+            _ => self,
+        }
+    }
+
+    /// Records that this error occurred after reading `offset` words, unless
+    /// a position was already recorded.
+    pub(crate) fn at(self, offset: usize) -> Self {
+        match self {
+            Self::DeserializeAt { .. } => self,
+            error => Self::DeserializeAt {
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
 /// A Result type for `risc0_zkvm::serde` operations that can fail
 pub type Result<T> = core::result::Result<T, Error>;
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut Formatter) -> core::fmt::Result {
         formatter.write_str(match self {
-            // Self::Custom(msg) => msg,
+            Self::Custom(msg) => msg,
             Self::DeserializeBadBool => "Found a bool that wasn't 0 or 1",
             Self::DeserializeBadChar => "Found an invalid unicode char",
             Self::DeserializeBadOption => "Found an Option discriminant that wasn't 0 or 1",
+            Self::DeserializeBadTag => "Found a type tag that wasn't expected",
             Self::DeserializeBadUtf8 => "Tried to parse invalid utf-8",
             Self::DeserializeUnexpectedEnd => "Unexpected end during deserialization",
+            Self::DeserializeTooDeep => "Found values nested too deeply",
+            Self::DeserializeAt { offset, error } => {
+                return write!(formatter, "{error} (after reading {offset} words)")
+            }
             Self::NotSupported => "Not supported",
             Self::SerializeBufferFull => "The serialize buffer is full",
         })
@@ -54,16 +99,14 @@ impl Display for Error {
 }
 
 impl serde::ser::Error for Error {
-    fn custom<T: Display>(_msg: T) -> Self {
-        // Error::Custom(msg.to_string())
-        Error::NotSupported
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
     }
 }
 
 impl serde::de::Error for Error {
-    fn custom<T: Display>(_msg: T) -> Self {
-        // Error::Custom(msg.to_string())
-        Error::NotSupported
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
     }
 }
 
@@ -29,6 +29,29 @@
 //! assert_eq!(input, output);
 //! ```
 //!
+//! The default encoding is compact: it contains only the values themselves,
+//! so reading it relies on the type being read to know what comes next. This
+//! doesn't support types that inspect the data to decide how to deserialize
+//! it, such as enums with `#[serde(untagged)]` or `#[serde(tag = "...")]`,
+//! structs with `#[serde(flatten)]` fields, and dynamically typed values like
+//! `serde_json::Value`. These can instead be sent in a self-describing
+//! encoding, which tags each value with its type and records the names of
+//! struct fields and enum variants, using [to_vec_tagged] and
+//! [from_slice_tagged] on the host and [`env::read_tagged`] in the guest:
+//! ```rust
+//! use risc0_zkvm::serde::{from_slice_tagged, to_vec_tagged};
+//! #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+//! #[serde(untagged)]
+//! enum Id {
+//!     Number(u32),
+//!     Name(String),
+//! }
+//! let input = vec![Id::Number(7), Id::Name("seven".into())];
+//! let encoded = to_vec_tagged(&input).unwrap();
+//! let output: Vec<Id> = from_slice_tagged(&encoded).unwrap();
+//! assert_eq!(input, output);
+//! ```
+//!
 //! On the guest side, the necessary (de)serialization functionality is
 //! included in [`env`] module functions such as [`env::read`] and
 //! [`env::commit`], so this crate rarely needs to be directly used in the
@@ -37,20 +60,24 @@
 //! [`env`]: ../guest/env/index.html
 //! [`env::commit`]: ../guest/env/fn.commit.html
 //! [`env::read`]: ../guest/env/fn.read.html
+//! [`env::read_tagged`]: ../guest/env/fn.read_tagged.html
 
 mod deserializer;
 mod err;
 mod serializer;
+mod tag;
 
-pub use deserializer::{from_slice, Deserializer, WordRead};
+pub use deserializer::{from_slice, from_slice_tagged, Deserializer, WordRead};
 pub use err::{Error, Result};
-pub use serializer::{to_vec, to_vec_with_capacity, Serializer, WordWrite};
+pub use serializer::{to_vec, to_vec_tagged, to_vec_with_capacity, Serializer, WordWrite};
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
-    use crate::serde::{from_slice, to_vec};
+    use serde::{Deserialize, Serialize};
+
+    use crate::serde::{from_slice, from_slice_tagged, to_vec, to_vec_tagged, Error};
 
     #[test]
     fn test_vec_round_trip() {
@@ -76,4 +103,144 @@ mod tests {
         let output: (u32, u64) = from_slice(data.as_slice()).unwrap();
         assert_eq!(input, output);
     }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Line(i64, i64),
+        Rect { width: u8, height: u16 },
+    }
+
+    #[test]
+    fn test_tagged_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Test {
+            flag: bool,
+            letter: char,
+            big: u64,
+            maybe: Option<String>,
+            shapes: Vec<Shape>,
+            pair: (i8, f64),
+            map: BTreeMap<u32, ()>,
+        }
+
+        let input = Test {
+            flag: true,
+            letter: 'x',
+            big: u64::MAX,
+            maybe: Some("text".into()),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Line(-1, 1),
+                Shape::Rect {
+                    width: 2,
+                    height: 3,
+                },
+            ],
+            pair: (-8, 2.5),
+            map: BTreeMap::from([(1, ()), (2, ())]),
+        };
+        let data = to_vec_tagged(&input).unwrap();
+        let output: Test = from_slice_tagged(&data).unwrap();
+        assert_eq!(input, output);
+    }
+
+    #[test]
+    fn test_tagged_serde_attributes() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(untagged)]
+        enum Untagged {
+            Number(u32),
+            // Unit variants are encoded as their name, so this must come before
+            // `Text` to be tried first.
+            Shape(Shape),
+            Text(String),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(tag = "type")]
+        enum Internal {
+            A { a: u32 },
+            B { b: String },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Inner {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Outer {
+            name: String,
+            #[serde(flatten)]
+            inner: Inner,
+            #[serde(skip_serializing_if = "Option::is_none", default)]
+            extra: Option<u32>,
+            #[serde(flatten)]
+            rest: HashMap<String, Untagged>,
+        }
+
+        let input = vec![
+            Untagged::Number(1),
+            Untagged::Text("two".into()),
+            Untagged::Shape(Shape::Empty),
+            Untagged::Shape(Shape::Rect {
+                width: 4,
+                height: 5,
+            }),
+        ];
+        let data = to_vec_tagged(&input).unwrap();
+        assert_eq!(input, from_slice_tagged::<Vec<Untagged>, _>(&data).unwrap());
+
+        let input = vec![Internal::A { a: 1 }, Internal::B { b: "b".into() }];
+        let data = to_vec_tagged(&input).unwrap();
+        assert_eq!(input, from_slice_tagged::<Vec<Internal>, _>(&data).unwrap());
+
+        let input = Outer {
+            name: "outer".into(),
+            inner: Inner { x: -1, y: 1 },
+            extra: None,
+            rest: HashMap::from([("z".into(), Untagged::Number(3))]),
+        };
+        let data = to_vec_tagged(&input).unwrap();
+        assert_eq!(input, from_slice_tagged(&data).unwrap());
+
+        // These types can't be written in the compact encoding, or can't be read
+        // back from it.
+        assert_eq!(to_vec(&input), Err(Error::NotSupported));
+        let data = to_vec(&Untagged::Number(1)).unwrap();
+        let err = from_slice::<Untagged, _>(&data).unwrap_err();
+        assert_eq!(err.kind(), &Error::NotSupported);
+    }
+
+    #[test]
+    fn test_tagged_json_value() {
+        let input = serde_json::json!({
+            "id": 7,
+            "name": "seven",
+            "tags": ["a", "b"],
+            "score": -1.5,
+            "parent": null,
+            "ok": true,
+        });
+        let data = to_vec_tagged(&input).unwrap();
+        let output: serde_json::Value = from_slice_tagged(&data).unwrap();
+        assert_eq!(input, output);
+    }
+
+    #[test]
+    fn test_tagged_errors() {
+        let data = to_vec_tagged(&(1u32, 2u32, 3u32)).unwrap();
+        let err = from_slice_tagged::<(u32, u32), _>(&data).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeBadTag);
+
+        let data = to_vec_tagged(&"text").unwrap();
+        assert!(from_slice_tagged::<u32, _>(&data).is_err());
+
+        let err = from_slice_tagged::<u32, _>(&[99, 0]).unwrap_err();
+        assert_eq!(err.kind(), &Error::DeserializeBadTag);
+    }
 }
@@ -16,7 +16,10 @@ use alloc::vec::Vec;
 
 use risc0_zkvm_platform::WORD_SIZE;
 
-use super::err::{Error, Result};
+use super::{
+    err::{Error, Result},
+    tag,
+};
 
 /// A writer for writing streams preferring word-based data.
 pub trait WordWrite {
@@ -91,9 +94,24 @@ where
     Ok(vec)
 }
 
+/// Serialize to a vector of u32 words in the self-describing encoding
+///
+/// The result can be read with [from_slice_tagged](super::from_slice_tagged),
+/// or by the guest with `env::read_tagged`.
+pub fn to_vec_tagged<'a, T>(value: &'a T) -> Result<Vec<u32>>
+where
+    T: serde::Serialize + ?Sized,
+{
+    let mut vec: Vec<u32> = Vec::with_capacity(core::mem::size_of_val(value));
+    let mut serializer = Serializer::new_tagged(&mut vec);
+    value.serialize(&mut serializer)?;
+    Ok(vec)
+}
+
 /// Enables serializing to a stream
 pub struct Serializer<W: WordWrite> {
     stream: W,
+    tagged: bool,
 }
 
 impl<W: WordWrite> Serializer<W> {
@@ -101,7 +119,58 @@ impl<W: WordWrite> Serializer<W> {
     ///
     /// Creates a serializer that writes to `stream`.
     pub fn new(stream: W) -> Self {
-        Serializer { stream }
+        Serializer {
+            stream,
+            tagged: false,
+        }
+    }
+
+    /// Construct a Serializer for the self-describing encoding
+    ///
+    /// Creates a serializer that writes to `stream`, prefixing each value with
+    /// a type tag, and writing the names of struct fields and enum variants.
+    /// This is larger than the compact encoding, but supports types that need
+    /// `Deserializer::deserialize_any`, such as those using
+    /// `#[serde(untagged)]` or `#[serde(flatten)]`.
+    pub fn new_tagged(stream: W) -> Self {
+        Serializer {
+            stream,
+            tagged: true,
+        }
+    }
+
+    fn write_tag(&mut self, tag: u32) -> Result<()> {
+        if self.tagged {
+            self.stream.write_words(&[tag])?;
+        }
+        Ok(())
+    }
+
+    fn write_word(&mut self, word: u32) -> Result<()> {
+        self.stream.write_words(&[word])
+    }
+
+    fn write_dword(&mut self, dword: u64) -> Result<()> {
+        self.stream
+            .write_words(&[(dword & 0xFFFFFFFF) as u32, (dword >> 32) as u32])
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write_word(bytes.len() as u32)?;
+        self.stream.write_padded_bytes(bytes)
+    }
+
+    // Writes the start of a variant with contents. In the compact encoding this
+    // is just the variant index; in the self-describing encoding it opens a map
+    // from the variant name to its contents.
+    fn write_variant(&mut self, variant_index: u32, variant: &'static str) -> Result<()> {
+        if self.tagged {
+            self.write_tag(tag::MAP)?;
+            self.write_tag(tag::STR)?;
+            self.write_bytes(variant.as_bytes())
+        } else {
+            self.write_word(variant_index)
+        }
     }
 }
 
@@ -128,7 +197,8 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.serialize_u8(if v { 1 } else { 0 })
+        self.write_tag(tag::BOOL)?;
+        self.write_word(if v { 1 } else { 0 })
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
@@ -140,11 +210,13 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        self.serialize_u32(v as u32)
+        self.write_tag(tag::I32)?;
+        self.write_word(v as u32)
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.serialize_u64(v as u64)
+        self.write_tag(tag::I64)?;
+        self.write_dword(v as u64)
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -156,30 +228,33 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_u32(self, v: u32) -> Result<()> {
-        self.stream.write_words(&[v])
+        self.write_tag(tag::U32)?;
+        self.write_word(v)
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.serialize_u32((v & 0xFFFFFFFF) as u32)?;
-        self.serialize_u32(((v >> 32) & 0xFFFFFFFF) as u32)
+        self.write_tag(tag::U64)?;
+        self.write_dword(v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
-        self.serialize_u32(v.to_bits())
+        self.write_tag(tag::F32)?;
+        self.write_word(v.to_bits())
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.serialize_u64(f64::to_bits(v))
+        self.write_tag(tag::F64)?;
+        self.write_dword(f64::to_bits(v))
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.serialize_u32(v as u32)
+        self.write_tag(tag::CHAR)?;
+        self.write_word(v as u32)
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        let bytes = v.as_bytes();
-        self.serialize_u32(bytes.len() as u32)?;
-        self.stream.write_padded_bytes(bytes)
+        self.write_tag(tag::STR)?;
+        self.write_bytes(v.as_bytes())
     }
 
     // NOTE: Serializing byte slices _does not_ currently call serialize_bytes. This
@@ -192,37 +267,49 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     // B) Use the experimental Rust specialization
     //    features.
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        self.serialize_u32(v.len() as u32)?;
-        self.stream.write_padded_bytes(v)
+        self.write_tag(tag::BYTES)?;
+        self.write_bytes(v)
     }
 
     fn serialize_none(self) -> Result<()> {
-        self.serialize_u32(0)
+        if self.tagged {
+            self.write_tag(tag::NONE)
+        } else {
+            self.write_word(0)
+        }
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<()>
     where
         T: serde::Serialize + ?Sized,
     {
-        self.serialize_u32(1)?;
+        if self.tagged {
+            self.write_tag(tag::SOME)?;
+        } else {
+            self.write_word(1)?;
+        }
         value.serialize(self)
     }
 
     fn serialize_unit(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::UNIT)
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
-        Ok(())
+        self.serialize_unit()
     }
 
     fn serialize_unit_variant(
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<()> {
-        self.serialize_u32(variant_index)
+        if self.tagged {
+            self.serialize_str(variant)
+        } else {
+            self.write_word(variant_index)
+        }
     }
 
     fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
@@ -236,20 +323,25 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: serde::Serialize + ?Sized,
     {
-        self.serialize_u32(variant_index)?;
-        value.serialize(self)
+        self.write_variant(variant_index, variant)?;
+        value.serialize(&mut *self)?;
+        self.write_tag(tag::END)
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        if self.tagged {
+            self.write_tag(tag::SEQ)?;
+            return Ok(self);
+        }
         match len {
             Some(val) => {
-                self.serialize_u32(val.try_into().unwrap())?;
+                self.write_word(val.try_into().unwrap())?;
                 Ok(self)
             }
             None => Err(Error::NotSupported),
@@ -257,6 +349,7 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        self.write_tag(tag::SEQ)?;
         Ok(self)
     }
 
@@ -265,6 +358,7 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.write_tag(tag::SEQ)?;
         Ok(self)
     }
 
@@ -272,17 +366,22 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.serialize_u32(variant_index)?;
+        self.write_variant(variant_index, variant)?;
+        self.write_tag(tag::SEQ)?;
         Ok(self)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        if self.tagged {
+            self.write_tag(tag::MAP)?;
+            return Ok(self);
+        }
         match len {
             Some(val) => {
-                self.serialize_u32(val.try_into().unwrap())?;
+                self.write_word(val.try_into().unwrap())?;
                 Ok(self)
             }
             None => Err(Error::NotSupported),
@@ -290,6 +389,7 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.write_tag(tag::MAP)?;
         Ok(self)
     }
 
@@ -297,10 +397,11 @@ impl<'a, W: WordWrite> serde::ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.serialize_u32(variant_index)?;
+        self.write_variant(variant_index, variant)?;
+        self.write_tag(tag::MAP)?;
         Ok(self)
     }
 }
@@ -317,7 +418,7 @@ impl<'a, W: WordWrite> serde::ser::SerializeSeq for &'a mut Serializer<W> {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::END)
     }
 }
 
@@ -333,7 +434,7 @@ impl<'a, W: WordWrite> serde::ser::SerializeTuple for &'a mut Serializer<W> {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::END)
     }
 }
 
@@ -349,7 +450,7 @@ impl<'a, W: WordWrite> serde::ser::SerializeTupleStruct for &'a mut Serializer<W
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::END)
     }
 }
 
@@ -365,7 +466,9 @@ impl<'a, W: WordWrite> serde::ser::SerializeTupleVariant for &'a mut Serializer<
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        // Close both the tuple and the map around the variant.
+        self.write_tag(tag::END)?;
+        self.write_tag(tag::END)
     }
 }
 
@@ -388,7 +491,7 @@ impl<'a, W: WordWrite> serde::ser::SerializeMap for &'a mut Serializer<W> {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::END)
     }
 }
 
@@ -396,15 +499,18 @@ impl<'a, W: WordWrite> serde::ser::SerializeStruct for &'a mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
         T: serde::Serialize + ?Sized,
     {
+        if self.tagged {
+            serde::ser::Serializer::serialize_str(&mut **self, key)?;
+        }
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        self.write_tag(tag::END)
     }
 }
 
@@ -412,15 +518,20 @@ impl<'a, W: WordWrite> serde::ser::SerializeStructVariant for &'a mut Serializer
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
         T: serde::Serialize + ?Sized,
     {
+        if self.tagged {
+            serde::ser::Serializer::serialize_str(&mut **self, key)?;
+        }
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        // Close both the struct and the map around the variant.
+        self.write_tag(tag::END)?;
+        self.write_tag(tag::END)
     }
 }
 
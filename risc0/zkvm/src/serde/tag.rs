@@ -0,0 +1,48 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Type tags for the self-describing encoding.
+//!
+//! In the self-describing encoding, each value starts with one of these tags,
+//! followed by the same words as in the compact encoding:
+//!
+//! * [UNIT], [NONE] and [SOME] have no further words of their own, and [SOME]
+//!   is followed by the contained value.
+//! * [BOOL], [I32], [U32], [F32] and [CHAR] are followed by one word, and
+//!   [I64], [U64] and [F64] by two.
+//! * [STR] and [BYTES] are followed by a length in bytes and the padded bytes.
+//! * [SEQ] and [MAP] are followed by their elements, or alternating keys and
+//!   values, and then an [END] tag, so that their length need not be known up
+//!   front.
+//!
+//! Structs are encoded as maps from field names to values, unit enum variants
+//! as their name, and other enum variants as a map from their name to their
+//! contents.
+
+pub(crate) const UNIT: u32 = 0;
+pub(crate) const BOOL: u32 = 1;
+pub(crate) const I32: u32 = 2;
+pub(crate) const I64: u32 = 3;
+pub(crate) const U32: u32 = 4;
+pub(crate) const U64: u32 = 5;
+pub(crate) const F32: u32 = 6;
+pub(crate) const F64: u32 = 7;
+pub(crate) const CHAR: u32 = 8;
+pub(crate) const STR: u32 = 9;
+pub(crate) const BYTES: u32 = 10;
+pub(crate) const NONE: u32 = 11;
+pub(crate) const SOME: u32 = 12;
+pub(crate) const SEQ: u32 = 13;
+pub(crate) const MAP: u32 = 14;
+pub(crate) const END: u32 = 15;
@@ -47,6 +47,9 @@ pub struct Session {
 
     /// The [ExitCode] of the session.
     pub exit_code: ExitCode,
+
+    /// The public input provided by the host.
+    pub input: Vec<u8>,
 }
 
 /// A reference to a [Segment].
@@ -76,6 +79,7 @@ pub struct Segment {
     pub(crate) syscalls: Vec<SyscallRecord>,
     pub(crate) split_insn: Option<u32>,
     pub(crate) exit_code: ExitCode,
+    pub(crate) input: Digest,
 
     /// The number of cycles in powers of 2.
     pub po2: usize,
@@ -88,12 +92,25 @@ pub struct Segment {
 }
 
 impl Session {
-    /// Construct a new [Session] from its constituent components.
+    /// Construct a new [Session] from its constituent components, without any
+    /// public input.
     pub fn new(segments: Vec<Box<dyn SegmentRef>>, journal: Vec<u8>, exit_code: ExitCode) -> Self {
+        Self::new_with_input(segments, journal, exit_code, Vec::new())
+    }
+
+    /// Construct a new [Session] from its constituent components, including
+    /// the public input whose digest its segments were executed with.
+    pub fn new_with_input(
+        segments: Vec<Box<dyn SegmentRef>>,
+        journal: Vec<u8>,
+        exit_code: ExitCode,
+        input: Vec<u8>,
+    ) -> Self {
         Self {
             segments,
             journal,
             exit_code,
+            input,
         }
     }
 
@@ -115,6 +132,7 @@ impl Segment {
         faults: PageFaults,
         syscalls: Vec<SyscallRecord>,
         exit_code: ExitCode,
+        input: Digest,
         split_insn: Option<u32>,
         po2: usize,
         index: u32,
@@ -126,6 +144,7 @@ impl Segment {
             faults,
             syscalls,
             exit_code,
+            input,
             split_insn,
             po2,
             index,
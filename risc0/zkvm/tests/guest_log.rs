@@ -0,0 +1,111 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The host's logger is process-wide, so this lives in its own test binary to
+// capture the records forwarded from the guest.
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use risc0_zkvm::{
+    serde::{from_slice, to_vec},
+    Executor, ExecutorEnv,
+};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+#[derive(Debug)]
+struct Captured {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    msg: String,
+}
+
+struct CaptureLogger(Mutex<Vec<Captured>>);
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push(Captured {
+            level: record.level(),
+            target: record.target().to_string(),
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            msg: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+#[test]
+fn guest_log_level() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let run = |level| {
+        LOGGER.0.lock().unwrap().clear();
+        let env = ExecutorEnv::builder()
+            .add_input(&to_vec(&MultiTestSpec::Log).unwrap())
+            .log_level(level)
+            .build();
+        let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        let max_level = from_slice::<u32, _>(&session.journal).unwrap();
+        let records = std::mem::take(&mut *LOGGER.0.lock().unwrap());
+        let records: Vec<_> = records
+            .into_iter()
+            .filter(|record| record.target == "guest")
+            .collect();
+        (max_level, records)
+    };
+
+    let (max_level, records) = run(LevelFilter::Off);
+    assert_eq!(max_level, LevelFilter::Off as u32);
+    assert!(records.is_empty());
+
+    let (max_level, records) = run(LevelFilter::Info);
+    assert_eq!(max_level, LevelFilter::Info as u32);
+    let levels: Vec<_> = records.iter().map(|record| record.level).collect();
+    assert_eq!(levels, [Level::Error, Level::Warn, Level::Info]);
+    let mut prev_cycle = 0;
+    for (record, expected) in records.iter().zip(["error", "warn", "info"]) {
+        assert_eq!(record.module_path.as_deref(), Some("multi_test"));
+        assert!(record.file.as_ref().unwrap().ends_with("multi_test.rs"));
+        let (cycle, msg) = record
+            .msg
+            .strip_prefix("R0VM[")
+            .and_then(|msg| msg.split_once("] "))
+            .unwrap_or_else(|| panic!("Unexpected message: {:?}", record.msg));
+        let cycle: usize = cycle.parse().unwrap();
+        assert!(cycle > prev_cycle);
+        prev_cycle = cycle;
+        assert_eq!(msg, expected);
+    }
+    // The records are logged on consecutive lines of the guest.
+    let lines: Vec<_> = records.iter().map(|record| record.line.unwrap()).collect();
+    assert_eq!(lines, [lines[0], lines[0] + 1, lines[0] + 2]);
+
+    let (max_level, records) = run(LevelFilter::Trace);
+    assert_eq!(max_level, LevelFilter::Trace as u32);
+    assert_eq!(records.len(), 5);
+}
@@ -0,0 +1,47 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The HAL memory tracker is process-wide, so this lives in its own test binary
+// to keep other proofs from contributing to the observed peak.
+
+use risc0_core::field::baby_bear::BabyBear;
+use risc0_zkp::core::hash::sha::{cpu::Impl as CpuImpl, Sha256HashSuite};
+use risc0_zkvm::{
+    prove::{cpu::estimate_peak_memory, get_prover},
+    serde::to_vec,
+    Executor, ExecutorEnv,
+};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+#[test]
+fn estimate_matches_cpu_hal() {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let env = ExecutorEnv::builder().add_input(&input).build();
+    let mut exec = Executor::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let session = exec.run().unwrap();
+    let segments = session.resolve().unwrap();
+    assert_eq!(segments.len(), 1);
+
+    let prover = get_prover("cpu");
+    prover.prove_segment(&segments[0]).unwrap();
+
+    let actual = prover.get_peak_memory_usage();
+    let estimate = estimate_peak_memory::<Sha256HashSuite<BabyBear, CpuImpl>>(segments[0].po2);
+    let error = actual.abs_diff(estimate) as f64 / actual as f64;
+    assert!(
+        error < 0.05,
+        "estimate {estimate} differs from actual {actual} by {:.1}%",
+        error * 100.0
+    );
+}
@@ -226,7 +226,8 @@ impl Validator {
         }
 
         if let Some(r0_path) = &self.context.risc0_path {
-            let r0_template_path = Path::new(r0_path).join("templates").join("rust-starter");
+            let r0_template_path =
+                Path::new(r0_path).join("risc0/cargo-risczero/templates/rust-starter");
             if !r0_template_path.exists() {
                 bail!(
                     "Failed to find {} on disk",